@@ -1,13 +1,11 @@
 use std::{cmp::Reverse, collections::BinaryHeap};
 
-use proconio::input;
+use opt_div::{scan, FastScanner};
 
 fn main() {
-    input! {
-        n: usize,
-        m: usize,
-        uvb: [(usize, usize, u64); m],
-    }
+    let mut sc = FastScanner::new(std::io::stdin().lock());
+    let (n, m) = scan!(sc => usize, usize);
+    let uvb = scan!(sc => [(usize, usize, u64); m]);
 
     let mut adj_list = vec![vec![]; n];
     for &(u, v, b) in &uvb {
@@ -1,14 +1,12 @@
 use rand::seq::SliceRandom;
-use proconio::input;
+use opt_div::{scan, FastScanner};
 
 fn main() {
     let mut rng = rand::thread_rng();
-    input! {
-        n: usize,
-        mut names: [String; n],
-        m: usize,
-        k: usize,
-    }
+    let mut sc = FastScanner::new(std::io::stdin().lock());
+    let n = scan!(sc => usize);
+    let names = scan!(sc => [String; n]);
+    let (m, k) = scan!(sc => usize, usize);
 
     let mut s = names.iter().flat_map(|name| name.chars()).collect::<Vec<_>>();
     for _ in 0..m {
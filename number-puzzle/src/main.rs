@@ -2,6 +2,7 @@
 use std::{collections::HashMap, fmt::Debug, num::NonZeroU8, ops::{Add, Div, Mul, Sub}};
 
 use num_traits::{Signed, Zero};
+use opt_div::{scan, FastScanner};
 use tinyvec::ArrayVec;
 
 struct GroupIter<const BUF_SIZE: usize> {
@@ -56,46 +57,6 @@ fn group_iter<const BUF_SIZE: usize>(n: u8) -> GroupIter<BUF_SIZE> {
     }
 }
 
-pub mod input {
-    use std::{
-        cell::RefCell,
-        fmt::Debug,
-        io::Read,
-        str::{FromStr, SplitWhitespace},
-    };
-
-    fn tokens_init() -> RefCell<SplitWhitespace<'static>> {
-        let mut buf = String::new();
-        std::io::stdin().read_to_string(&mut buf).unwrap();
-        RefCell::new(String::leak(buf).split_whitespace())
-    }
-
-    fn next_token() -> Option<&'static str> {
-        thread_local! {
-            static TOKENS: RefCell<SplitWhitespace<'static>> = tokens_init();
-        }
-        TOKENS.with_borrow_mut(|tokens| tokens.next())
-    }
-
-    #[allow(dead_code)]
-    pub fn scan<T: FromStr>() -> Option<T>
-    where
-        T::Err: Debug,
-    {
-        next_token().map(|s| s.parse().unwrap())
-    }
-
-    #[macro_export]
-    macro_rules! scan {
-        ($t:ty $(,)?) => {
-            $crate::input::scan::<$t>().unwrap()
-        };
-        ($($t:ty),+ $(,)?) => {
-            ($($crate::input::scan::<$t>().unwrap()),*)
-        };
-    }
-}
-
 #[derive(Clone, Copy, Debug)]
 struct Fraction<T> {
     numerator: T,
@@ -275,9 +236,10 @@ fn calculate(expr: &[Node]) -> Option<Fraction<i64>> {
 }
 
 fn main() {
-    let n = scan!(u8);
+    let mut sc = FastScanner::new(std::io::stdin().lock());
+    let n = scan!(sc => u8);
     assert!(1 <= n && n as usize <= MAX_N);
-    let numbers = (0..n).map(|_| scan!(i32)).collect::<Vec<_>>();
+    let numbers = scan!(sc => [i32; n]);
     let mut num_count = HashMap::new();
     for &num in &numbers {
         num_count.entry(num).and_modify(|x| *x += 1u32).or_insert(1);
@@ -1,49 +1,211 @@
-use std::hint::black_box;
+//! 変更前後を比べるときは、変更前のコミットで基準を保存してから変更後を測る。基準は
+//! `target/criterion/*/main`に置かれてコミットしないので、同じマシンで続けて測る。
+//!
+//! ```text
+//! git stash && cargo bench -p opt-div --bench benchmark -- --save-baseline main
+//! git stash pop && cargo bench -p opt-div --bench benchmark -- --baseline main
+//! ```
+//!
+//! 名前で絞るときは`-- "string to integer" --baseline main`のように前に書く。
+//!
+//! `parse_fast`が`str::parse`より遅くなっていないかは`--bench regression`で確かめる。
 
-use criterion::{criterion_group, criterion_main, Criterion};
+use std::{fmt::{Debug, Display}, hint::black_box, io::Write, ops::{Div, Rem}, str::FromStr};
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
 use rand::{distributions::{Distribution, Standard}, rngs::StdRng, Rng, SeedableRng};
-use opt_div::FromStrFast;
+use opt_div::{__bench::{parse16, parse16_swar}, parse_u64_many, Divisor, DivisorInt, FastScanner, FromStrFast, ToStrFast};
+
+mod corpus;
 
-fn random_integer_string<I: ToString, R: Rng>(n: usize, rng: &mut R) -> Vec<String>
+/// `parse_fast`と`parse_fast_unchecked`の差が数字の検証と桁あふれ検査のコスト
+fn bench_type<I>(c: &mut Criterion, name: &str, data: Vec<String>)
 where
-    Standard: Distribution<I>,
+    I: FromStr + FromStrFast,
+    <I as FromStr>::Err: Debug,
+    <I as FromStrFast>::Err: Debug,
 {
-    (0..n).map(|_| rng.gen::<I>().to_string()).collect()
-}
-
-fn bench_fast_parse(c: &mut Criterion) {
-    let mut rng = StdRng::seed_from_u64(100);
-    let data = random_integer_string::<u32, _>(100000, &mut rng);
-    c.benchmark_group("string to integer").bench_function("FromStr::parse", |b| {
+    c.benchmark_group(format!("string to integer ({name})")).bench_function("FromStr::parse", |b| {
         b.iter(|| {
             for s in &data {
                 let s = black_box(s.as_str());
-                black_box(s.parse::<u32>().unwrap());
+                black_box(s.parse::<I>().unwrap());
             }
         })
     }).bench_function("FromStrFast::parse_fast", |b| {
         b.iter(|| {
             for s in &data {
                 let s = black_box(s.as_str());
-                black_box(u32::parse_fast(s).unwrap());
+                black_box(I::parse_fast(s).unwrap());
             }
         })
     }).bench_function("FromStr::parse unchecked", |b| {
         b.iter(|| {
             for s in &data {
                 let s = black_box(s.as_str());
-                black_box(unsafe { s.parse::<u32>().unwrap_unchecked() });
+                black_box(unsafe { s.parse::<I>().unwrap_unchecked() });
             }
         })
     }).bench_function("FromStrFast::parse_fast unchecked", |b| {
         b.iter(|| {
             for s in &data {
                 let s = black_box(s.as_str());
-                black_box(unsafe { u32::parse_fast_unchecked(s) });
+                black_box(unsafe { I::parse_fast_unchecked(s) });
+            }
+        })
+    });
+}
+
+/// 出力用のバッファに書き込む場合を`write!`と比べる
+fn bench_format<I>(c: &mut Criterion, name: &str)
+where
+    I: ToStrFast + Display,
+    Standard: Distribution<I>,
+{
+    let mut rng = StdRng::seed_from_u64(100);
+    let data = (0..100000).map(|_| rng.gen::<I>()).collect::<Vec<_>>();
+    let mut out = Vec::with_capacity(data.len() * 21);
+    c.benchmark_group(format!("integer to string ({name})")).bench_function("ToString::to_string", |b| {
+        b.iter(|| {
+            for &n in &data {
+                black_box(black_box(n).to_string());
+            }
+        })
+    }).bench_function("write!", |b| {
+        b.iter(|| {
+            out.clear();
+            for &n in &data {
+                write!(out, "{}", black_box(n)).unwrap();
+            }
+            black_box(&out);
+        })
+    }).bench_function("ToStrFast::write_fast", |b| {
+        b.iter(|| {
+            out.clear();
+            for &n in &data {
+                black_box(n).write_fast(&mut out).unwrap();
+            }
+            black_box(&out);
+        })
+    }).bench_function("ToStrFast::format_fast", |b| {
+        let mut buf = [0; 40];
+        b.iter(|| {
+            for &n in &data {
+                black_box(black_box(n).format_fast(&mut buf));
+            }
+        })
+    });
+}
+
+/// 除数は実行時に決まるので`/`はハードウェアの除算命令になる
+fn bench_divide<I>(c: &mut Criterion, name: &str)
+where
+    I: DivisorInt + Div<Output = I> + Rem<Output = I> + Div<Divisor<I>, Output = I> + Rem<Divisor<I>, Output = I>,
+    Standard: Distribution<I>,
+{
+    let mut rng = StdRng::seed_from_u64(100);
+    let data = (0..100000).map(|_| rng.gen::<I>()).collect::<Vec<_>>();
+    let d = loop {
+        if let Some(d) = Divisor::try_new(rng.gen::<I>()) {
+            break d;
+        }
+    };
+    c.benchmark_group(format!("division by invariant ({name})")).bench_function("operators", |b| {
+        let divisor = d.get();
+        b.iter(|| {
+            let divisor = black_box(divisor);
+            for &n in &data {
+                black_box((n / divisor, n % divisor));
+            }
+        })
+    }).bench_function("Divisor (including Divisor::new)", |b| {
+        let divisor = d.get();
+        b.iter(|| {
+            let d = Divisor::new(black_box(divisor));
+            for &n in &data {
+                black_box((n / d, n % d));
+            }
+        })
+    });
+}
+
+/// 空白区切りの入力全体を読む
+fn bench_stream(c: &mut Criterion) {
+    let input = corpus::stream();
+    let mut group = c.benchmark_group("token stream (u32)");
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.bench_function("split_ascii_whitespace + FromStr::parse", |b| {
+        b.iter(|| {
+            for s in black_box(input.as_str()).split_ascii_whitespace() {
+                black_box(s.parse::<u32>().unwrap());
+            }
+        })
+    }).bench_function("FastScanner::scan", |b| {
+        b.iter(|| {
+            let mut sc = FastScanner::new(black_box(input.as_bytes()));
+            for _ in 0..corpus::LEN {
+                black_box(sc.scan::<u32>().unwrap());
+            }
+        })
+    });
+    group.finish();
+}
+
+/// SSE4.1とAVX2の経路をSWARと比べる。命令セットがなければどちらもSWARになる
+fn bench_simd(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(105);
+    let chunks = (0..corpus::LEN)
+        .map(|_| std::array::from_fn::<u8, 16, _>(|_| rng.gen_range(b'0'..=b'9')))
+        .collect::<Vec<_>>();
+    c.benchmark_group("16 digits").bench_function("SWAR", |b| {
+        b.iter(|| {
+            for s in &chunks {
+                black_box(parse16_swar(black_box(s)));
+            }
+        })
+    }).bench_function("SSE4.1", |b| {
+        b.iter(|| {
+            for s in &chunks {
+                black_box(parse16(black_box(s)));
             }
         })
     });
+    // AVX2の経路は符号のない16桁以下の数字列だけを2つずつ読む。桁数は一様に選ぶ
+    let data = (0..corpus::LEN)
+        .map(|_| {
+            let digits = rng.gen_range(1..=16);
+            rng.gen_range(0..10u64.pow(digits)).to_string()
+        })
+        .collect::<Vec<_>>();
+    let data = data.iter().map(String::as_str).collect::<Vec<_>>();
+    c.benchmark_group("many u64 (16 digits or less)").bench_function("FromStrFast::parse_fast", |b| {
+        b.iter(|| black_box(&data).iter().map(|s| u64::parse_fast(s)).collect::<Vec<_>>())
+    }).bench_function("parse_u64_many (AVX2)", |b| {
+        b.iter(|| parse_u64_many(black_box(&data)))
+    });
+}
+
+fn bench_fast_parse(c: &mut Criterion) {
+    bench_type::<u32>(c, "u32", corpus::uniform::<u32>());
+    bench_type::<u32>(c, "short u32", corpus::short());
+    bench_type::<u32>(c, "mixed-length u32", corpus::mixed());
+    bench_type::<i64>(c, "signed i64", corpus::signed());
+    bench_type::<u64>(c, "u64", corpus::uniform::<u64>());
+    bench_stream(c);
+    bench_simd(c);
+}
+
+fn bench_fast_format(c: &mut Criterion) {
+    bench_format::<u32>(c, "u32");
+    bench_format::<u64>(c, "u64");
+}
+
+fn bench_fast_divide(c: &mut Criterion) {
+    bench_divide::<u32>(c, "u32");
+    bench_divide::<u64>(c, "u64");
+    bench_divide::<i32>(c, "i32");
+    bench_divide::<i64>(c, "i64");
 }
 
-criterion_group!(bench, bench_fast_parse);
+criterion_group!(bench, bench_fast_parse, bench_fast_format, bench_fast_divide);
 criterion_main!(bench);
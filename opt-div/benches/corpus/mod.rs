@@ -0,0 +1,65 @@
+//! ベンチマーク用の入力
+//!
+//! `benchmark`と`regression`で同じ入力を使う。どれも乱数の種を固定している。
+
+use rand::{distributions::{Distribution, Standard}, rngs::StdRng, Rng, SeedableRng};
+
+pub const LEN: usize = 100000;
+
+/// 型の全域から一様に選んだ値。ほとんどが最大桁数に近い
+pub fn uniform<I: ToString>() -> Vec<String>
+where
+    Standard: Distribution<I>,
+{
+    let mut rng = StdRng::seed_from_u64(100);
+    (0..LEN).map(|_| rng.gen::<I>().to_string()).collect()
+}
+
+/// 桁数が一様に分布する`digits`桁以下の数字列。先頭は0でない
+fn random_digits<R: Rng>(rng: &mut R, digits: u32) -> u64 {
+    let len = rng.gen_range(1..=digits);
+    rng.gen_range(10u64.pow(len - 1)..10u64.pow(len)) - (len == 1) as u64
+}
+
+/// 4桁以下の`u32`
+pub fn short() -> Vec<String> {
+    let mut rng = StdRng::seed_from_u64(101);
+    (0..LEN).map(|_| random_digits(&mut rng, 4).to_string()).collect()
+}
+
+/// 1桁から10桁までの`u32`
+pub fn mixed() -> Vec<String> {
+    let mut rng = StdRng::seed_from_u64(102);
+    (0..LEN)
+        .map(|_| loop {
+            if let Ok(n) = u32::try_from(random_digits(&mut rng, 10)) {
+                break n.to_string();
+            }
+        })
+        .collect()
+}
+
+/// 符号がランダムで、1桁から18桁までの`i64`
+pub fn signed() -> Vec<String> {
+    let mut rng = StdRng::seed_from_u64(103);
+    (0..LEN)
+        .map(|_| {
+            let n = random_digits(&mut rng, 18) as i64;
+            if rng.gen() { (-n).to_string() } else { n.to_string() }
+        })
+        .collect()
+}
+
+/// [`mixed`]と同じ値を空白と改行で区切った入力
+pub fn stream() -> String {
+    let mut rng = StdRng::seed_from_u64(104);
+    let mut s = String::new();
+    for (i, n) in mixed().iter().enumerate() {
+        if i > 0 {
+            s.push(if rng.gen_ratio(1, 8) { '\n' } else { ' ' });
+        }
+        s.push_str(n);
+    }
+    s.push('\n');
+    s
+}
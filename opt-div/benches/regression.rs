@@ -0,0 +1,97 @@
+//! `parse_fast`が`str::parse`より遅い入力がないか確かめる
+//!
+//! `cargo bench -p opt-div --bench regression`で実行し、どれか1つでも遅ければ失敗する。
+//! 速度の比は中央値と、その約97%信頼区間で表示する。
+
+use std::{hint::black_box, process::ExitCode, str::FromStr, time::{Duration, Instant}};
+
+use opt_div::{FastScanner, FromStrFast};
+
+mod corpus;
+
+const SAMPLES: usize = 21;
+
+/// 21個の標本の中央値の信頼区間は、小さい方から6番目と16番目の間 (信頼度は約97%)
+const CI: (usize, usize) = (5, 15);
+
+/// 1回の時間
+fn time(f: &mut impl FnMut()) -> Duration {
+    let start = Instant::now();
+    f();
+    start.elapsed()
+}
+
+/// 入力全体を1回読む時間を比べて表示し、`fast`の方が遅ければ`false`
+///
+/// 負荷の変化が片方に偏らないよう2つを交互に測り、組ごとの時間の比を標本にする。比の中央値の
+/// 信頼区間が全て1を超えたときだけ遅いとみなし、ノイズでは失敗しないようにする。
+fn compare(name: &str, mut std: impl FnMut(), mut fast: impl FnMut()) -> bool {
+    for _ in 0..3 {
+        std();
+        fast();
+    }
+    let (mut std_times, mut fast_times, mut ratios) = (Vec::new(), Vec::new(), Vec::new());
+    for _ in 0..SAMPLES {
+        let (s, f) = (time(&mut std), time(&mut fast));
+        std_times.push(s);
+        fast_times.push(f);
+        ratios.push(f.as_secs_f64() / s.as_secs_f64());
+    }
+    std_times.sort_unstable();
+    fast_times.sort_unstable();
+    ratios.sort_unstable_by(f64::total_cmp);
+    let (std, fast) = (std_times[SAMPLES / 2], fast_times[SAMPLES / 2]);
+    let (ratio, low, high) = (ratios[SAMPLES / 2], ratios[CI.0], ratios[CI.1]);
+    let ok = low <= 1.0;
+    println!(
+        "{name:<20} str::parse {std:>10.2?}  parse_fast {fast:>10.2?}  {ratio:>5.2}x [{low:.2}, {high:.2}]  {}",
+        if ok { "ok" } else { "REGRESSION" }
+    );
+    ok
+}
+
+fn compare_type<I: FromStr + FromStrFast>(name: &str, data: &[String]) -> bool {
+    compare(
+        name,
+        || {
+            for s in data {
+                black_box(black_box(s.as_str()).parse::<I>().ok());
+            }
+        },
+        || {
+            for s in data {
+                black_box(I::parse_fast(black_box(s.as_str())).ok());
+            }
+        },
+    )
+}
+
+fn main() -> ExitCode {
+    let input = corpus::stream();
+    let results = [
+        compare_type::<u32>("u32", &corpus::uniform::<u32>()),
+        compare_type::<u32>("short u32", &corpus::short()),
+        compare_type::<u32>("mixed-length u32", &corpus::mixed()),
+        compare_type::<i64>("signed i64", &corpus::signed()),
+        compare_type::<u64>("u64", &corpus::uniform::<u64>()),
+        compare(
+            "token stream (u32)",
+            || {
+                for s in black_box(input.as_str()).split_ascii_whitespace() {
+                    black_box(s.parse::<u32>().ok());
+                }
+            },
+            || {
+                let mut sc = FastScanner::new(black_box(input.as_bytes()));
+                for _ in 0..corpus::LEN {
+                    black_box(sc.scan::<u32>().ok());
+                }
+            },
+        ),
+    ];
+    if results.contains(&false) {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -0,0 +1,80 @@
+//! 任意のバイト列で[`FromStrFast`]の全ての実装を標準ライブラリと比べる
+//!
+//! ```text
+//! cd opt-div/fuzz && cargo +nightly fuzz run from_str_fast
+//! ```
+
+#![no_main]
+
+use std::{fmt::Debug, str::FromStr};
+
+use libfuzzer_sys::fuzz_target;
+use opt_div::{FromStrFast, PaddedBytes, ParseIntError};
+
+/// 余白に数字を置き、`len`より後ろを読んでしまったら結果が変わるようにする
+fn padded(data: &[u8]) -> Vec<u8> {
+    let mut buf = data.to_vec();
+    buf.resize(data.len() + PaddedBytes::PADDING, b'9');
+    buf
+}
+
+/// 全ての入口で結果が`expected`と一致するか
+fn check<T>(data: &[u8], expected: Result<T, T::Err>)
+where
+    T: FromStrFast + PartialEq + Debug,
+    T::Err: PartialEq + Debug,
+{
+    let name = std::any::type_name::<T>();
+    if let Ok(s) = std::str::from_utf8(data) {
+        assert_eq!(T::parse_fast(s), expected, "{s:?} as {name}");
+    }
+    assert_eq!(T::parse_fast_bytes(data), expected, "{data:?} as {name}");
+    let buf = padded(data);
+    let s = PaddedBytes::new(&buf, data.len()).unwrap();
+    assert_eq!(T::parse_fast_padded(s), expected, "{data:?} as {name}");
+}
+
+/// UTF-8でないバイトは置換文字になるが、置換文字も数字でないので位置を含めて同じエラーになる
+fn check_int<T>(data: &[u8])
+where
+    T: FromStrFast<Err = ParseIntError> + FromStr<Err = std::num::ParseIntError> + PartialEq + Debug,
+{
+    let s = String::from_utf8_lossy(data);
+    let signed = "-1".parse::<T>().is_ok();
+    let expected = s.parse::<T>().map_err(|e| ParseIntError::from_std(&e, &s, signed).unwrap());
+    check(data, expected);
+}
+
+/// NaNも比べられるようにビット列で比べる
+macro_rules! check_float {
+    ($t:ty, $data:expr) => {{
+        let data: &[u8] = $data;
+        let bits = |r: Result<$t, _>| r.map(<$t>::to_bits);
+        let expected = bits(String::from_utf8_lossy(data).parse::<$t>());
+        if let Ok(s) = std::str::from_utf8(data) {
+            assert_eq!(bits(<$t>::parse_fast(s)), expected, "{s:?}");
+        }
+        assert_eq!(bits(<$t>::parse_fast_bytes(data)), expected, "{data:?}");
+        let buf = padded(data);
+        let s = PaddedBytes::new(&buf, data.len()).unwrap();
+        assert_eq!(bits(<$t>::parse_fast_padded(s)), expected, "{data:?}");
+    }};
+}
+
+fuzz_target!(|data: &[u8]| {
+    check_int::<u8>(data);
+    check_int::<u16>(data);
+    check_int::<u32>(data);
+    check_int::<u64>(data);
+    check_int::<u128>(data);
+    check_int::<usize>(data);
+    check_int::<i8>(data);
+    check_int::<i16>(data);
+    check_int::<i32>(data);
+    check_int::<i64>(data);
+    check_int::<i128>(data);
+    check_int::<isize>(data);
+    check_float!(f32, data);
+    check_float!(f64, data);
+    check(data, String::from_utf8(data.to_vec()).map_err(|e| e.utf8_error()));
+});
@@ -0,0 +1,265 @@
+//! 実行時に決まる除数での高速な除算
+//!
+//! 除数ごとに乗数とシフト量を前計算し、除算を乗算とシフトに置き換える (Granlund–Montgomery,
+//! libdivideの方式)。
+
+use std::ops::{Div, Rem};
+
+/// 前計算した除数
+///
+/// ```
+/// use opt_div::Divisor;
+///
+/// let d = Divisor::new(7u64);
+/// assert_eq!(d.div_rem(100), (14, 2));
+/// assert_eq!(100 / d, 14);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Divisor<T> {
+    divisor: T,
+    /// 0のときは`|divisor|`が2の冪で、シフトだけで割る
+    magic: T,
+    shift: u32,
+    /// 乗数が型に収まらず、暗黙の最上位bitの分だけ被除数を足す
+    add: bool,
+}
+
+/// [`Divisor`]で割れる整数型
+pub trait DivisorInt: Copy + sealed::Sealed {
+    #[doc(hidden)]
+    fn compute(divisor: Self) -> Option<Divisor<Self>>;
+    #[doc(hidden)]
+    fn div_rem(d: &Divisor<Self>, n: Self) -> (Self, Self);
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+impl<T: DivisorInt> Divisor<T> {
+    /// # Panics
+    /// `divisor == 0`のとき
+    pub fn new(divisor: T) -> Self {
+        Self::try_new(divisor).expect("attempt to divide by zero")
+    }
+
+    /// `divisor == 0`のときは`None`
+    pub fn try_new(divisor: T) -> Option<Self> {
+        T::compute(divisor)
+    }
+
+    #[inline]
+    pub fn get(&self) -> T {
+        self.divisor
+    }
+
+    /// 0方向に丸めた商
+    ///
+    /// 符号付きの`MIN / -1`は[`wrapping_div`](i32::wrapping_div)と同じく`MIN`になる。
+    #[inline]
+    pub fn div(&self, n: T) -> T {
+        self.div_rem(n).0
+    }
+
+    /// 被除数と同じ符号の剰余
+    #[inline]
+    pub fn rem(&self, n: T) -> T {
+        self.div_rem(n).1
+    }
+
+    #[inline]
+    pub fn div_rem(&self, n: T) -> (T, T) {
+        T::div_rem(self, n)
+    }
+}
+
+macro_rules! impl_unsigned {
+    ($($t:ty => $wide:ty),*) => {
+        $(
+            impl sealed::Sealed for $t {}
+
+            impl DivisorInt for $t {
+                fn compute(divisor: $t) -> Option<Divisor<$t>> {
+                    if divisor == 0 {
+                        return None;
+                    }
+                    let l = divisor.ilog2();
+                    if divisor.is_power_of_two() {
+                        return Some(Divisor { divisor, magic: 0, shift: l, add: false });
+                    }
+                    let d = divisor as $wide;
+                    let num = 1 << (<$t>::BITS + l);
+                    let (m, rem) = (num / d, num % d);
+                    // 2^lだけ小さいシフトで誤差が収まれば乗数は型に収まる
+                    let (magic, add) = if d - rem < 1 << l {
+                        (m + 1, false)
+                    } else {
+                        (2 * m + (2 * rem >= d) as $wide + 1, true)
+                    };
+                    Some(Divisor { divisor, magic: magic as $t, shift: l, add })
+                }
+
+                #[inline]
+                fn div_rem(d: &Divisor<$t>, n: $t) -> ($t, $t) {
+                    let q = if d.magic == 0 {
+                        n >> d.shift
+                    } else {
+                        let q = ((d.magic as $wide * n as $wide) >> <$t>::BITS) as $t;
+                        if d.add {
+                            (((n - q) >> 1) + q) >> d.shift
+                        } else {
+                            q >> d.shift
+                        }
+                    };
+                    (q, n - q * d.divisor)
+                }
+            }
+        )*
+        impl_ops!($($t),*);
+    };
+}
+
+macro_rules! impl_signed {
+    ($($t:ty => $unsigned:ty, $wide:ty, $uwide:ty),*) => {
+        $(
+            impl sealed::Sealed for $t {}
+
+            impl DivisorInt for $t {
+                fn compute(divisor: $t) -> Option<Divisor<$t>> {
+                    if divisor == 0 {
+                        return None;
+                    }
+                    let abs = divisor.unsigned_abs();
+                    let l = abs.ilog2();
+                    if abs.is_power_of_two() {
+                        return Some(Divisor { divisor, magic: 0, shift: l, add: false });
+                    }
+                    let d = abs as $uwide;
+                    let num = 1 << (<$t>::BITS - 1 + l);
+                    let (m, rem) = (num / d, num % d);
+                    let (magic, shift, add) = if d - rem < 1 << l {
+                        (m + 1, l - 1, false)
+                    } else {
+                        (2 * m + (2 * rem >= d) as $uwide + 1, l, true)
+                    };
+                    let magic = magic as $unsigned as $t;
+                    let magic = if divisor < 0 { magic.wrapping_neg() } else { magic };
+                    Some(Divisor { divisor, magic, shift, add })
+                }
+
+                #[inline]
+                fn div_rem(d: &Divisor<$t>, n: $t) -> ($t, $t) {
+                    let q = if d.magic == 0 {
+                        // 負の数は2^shift - 1を足してから算術シフトすると0方向に丸まる
+                        let mask = ((1 as $unsigned) << d.shift).wrapping_sub(1) as $t;
+                        let q = n.wrapping_add((n >> (<$t>::BITS - 1)) & mask) >> d.shift;
+                        if d.divisor < 0 { q.wrapping_neg() } else { q }
+                    } else {
+                        let mut q = ((d.magic as $wide * n as $wide) >> <$t>::BITS) as $t;
+                        if d.add {
+                            q = q.wrapping_add(if d.divisor < 0 { n.wrapping_neg() } else { n });
+                        }
+                        q >>= d.shift;
+                        q + (q < 0) as $t
+                    };
+                    (q, n.wrapping_sub(q.wrapping_mul(d.divisor)))
+                }
+            }
+        )*
+        impl_ops!($($t),*);
+    };
+}
+
+macro_rules! impl_ops {
+    ($($t:ty),*) => {
+        $(
+            impl Div<Divisor<$t>> for $t {
+                type Output = $t;
+                #[inline]
+                fn div(self, d: Divisor<$t>) -> $t {
+                    d.div(self)
+                }
+            }
+
+            impl Rem<Divisor<$t>> for $t {
+                type Output = $t;
+                #[inline]
+                fn rem(self, d: Divisor<$t>) -> $t {
+                    d.rem(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_unsigned!(u8 => u16, u16 => u32, u32 => u64, u64 => u128, usize => u128);
+impl_signed!(
+    i8 => u8, i16, u16,
+    i16 => u16, i32, u32,
+    i32 => u32, i64, u64,
+    i64 => u64, i128, u128,
+    isize => usize, i128, u128
+);
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+
+    macro_rules! check {
+        ($t:ty, $d:expr, $ns:expr) => {{
+            let d: $t = $d;
+            let divisor = Divisor::<$t>::new(d);
+            for n in $ns {
+                let n: $t = n;
+                assert_eq!(divisor.div_rem(n), (n.wrapping_div(d), n.wrapping_rem(d)), "{n} / {d}");
+            }
+        }};
+    }
+
+    #[test]
+    fn exhaustive_small() {
+        for d in 1..=u8::MAX {
+            check!(u8, d, 0..=u8::MAX);
+        }
+        for d in i8::MIN..=i8::MAX {
+            if d != 0 {
+                check!(i8, d, i8::MIN..=i8::MAX);
+            }
+        }
+        for d in 1..=1000 {
+            check!(u16, d, 0..=u16::MAX);
+            check!(i16, -(d as i16), i16::MIN..=i16::MAX);
+        }
+    }
+
+    #[test]
+    fn random() {
+        let mut rng = StdRng::seed_from_u64(46);
+        let edges32 = [0, 1, 2, 3, u32::MAX, u32::MAX - 1, 1 << 31, (1 << 31) - 1];
+        let edges64 = [0, 1, 2, 3, u64::MAX, u64::MAX - 1, 1 << 63, (1 << 63) - 1];
+        for _ in 0..2000 {
+            let shift = rng.gen_range(0..64);
+            let d64 = (rng.gen::<u64>() >> shift).max(1);
+            let d32 = (d64 >> 32) as u32 | 1 << rng.gen_range(0..32);
+            let ns = (0..50).map(|_| rng.gen::<u64>() >> rng.gen_range(0..64)).collect::<Vec<_>>();
+            for d in [d64, d64.checked_next_power_of_two().unwrap_or(1 << 63), d64 >> 1 | 1] {
+                check!(u64, d, ns.iter().copied().chain(edges64));
+                check!(usize, d as usize, ns.iter().map(|&n| n as usize));
+                check!(i64, d as i64 | 1, ns.iter().map(|&n| n as i64).chain(edges64.map(|n| n as i64)));
+                check!(i64, (d as i64).wrapping_neg().min(-1), ns.iter().map(|&n| n as i64));
+            }
+            for d in [d32, d32.checked_next_power_of_two().unwrap_or(1 << 31), d32 / 3 + 1] {
+                check!(u32, d, ns.iter().map(|&n| n as u32).chain(edges32));
+                check!(i32, d as i32 | 1, ns.iter().map(|&n| n as i32).chain(edges32.map(|n| n as i32)));
+                check!(i32, (d as i32).wrapping_neg().min(-1), ns.iter().map(|&n| n as i32));
+            }
+        }
+        for d in [i64::MIN, i64::MAX, -1, 1, i64::MIN + 1] {
+            check!(i64, d, [i64::MIN, i64::MAX, -1, 0, 1, i64::MIN + 1]);
+        }
+        assert_eq!(Divisor::<u32>::try_new(0), None);
+        assert_eq!(17i32 % Divisor::new(-5), 2);
+    }
+}
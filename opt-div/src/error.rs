@@ -0,0 +1,101 @@
+//! 変換と読み込みのエラー
+
+use std::{fmt, io, num::IntErrorKind};
+
+/// [`FromStrFast::parse_fast`](crate::FromStrFast::parse_fast)のエラー
+///
+/// [`std::num::ParseIntError`]と同じ場合に同じ種類のエラーになる。数字でないバイトの位置も持つ。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ParseIntError {
+    /// 空文字列
+    Empty,
+    /// `position`バイト目が数字でない
+    ///
+    /// 符号の後ろが空のときや、符号なし整数に`-`が付いているときは符号の位置(0)
+    InvalidDigit { position: usize },
+    /// 型の最大値より大きい
+    PosOverflow,
+    /// 型の最小値より小さい
+    NegOverflow,
+}
+
+impl ParseIntError {
+    /// 標準ライブラリのエラーと同じ分類
+    pub fn kind(&self) -> IntErrorKind {
+        match self {
+            Self::Empty => IntErrorKind::Empty,
+            Self::InvalidDigit { .. } => IntErrorKind::InvalidDigit,
+            Self::PosOverflow => IntErrorKind::PosOverflow,
+            Self::NegOverflow => IntErrorKind::NegOverflow,
+        }
+    }
+
+    /// 標準ライブラリのエラーを変換する。位置は元の文字列`s`から求める
+    ///
+    /// `signed`は変換先の型が符号付きか。符号なし整数では`-`が付いていれば位置は常に0になる。
+    /// `IntErrorKind::Zero`など対応する種類がないときは`None`
+    pub fn from_std(err: &std::num::ParseIntError, s: &str, signed: bool) -> Option<Self> {
+        Some(match err.kind() {
+            IntErrorKind::Empty => Self::Empty,
+            IntErrorKind::InvalidDigit if !signed && s.starts_with('-') => Self::InvalidDigit { position: 0 },
+            IntErrorKind::InvalidDigit => {
+                let digits = s.strip_prefix(['+', '-']).filter(|d| !d.is_empty()).unwrap_or(s);
+                let offset = s.len() - digits.len();
+                let position = digits.bytes().position(|c| !c.is_ascii_digit()).map_or(0, |i| offset + i);
+                Self::InvalidDigit { position }
+            }
+            IntErrorKind::PosOverflow => Self::PosOverflow,
+            IntErrorKind::NegOverflow => Self::NegOverflow,
+            _ => return None,
+        })
+    }
+}
+
+impl From<ParseIntError> for IntErrorKind {
+    fn from(err: ParseIntError) -> Self {
+        err.kind()
+    }
+}
+
+impl fmt::Display for ParseIntError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "cannot parse integer from empty string"),
+            Self::InvalidDigit { position } => write!(f, "invalid digit found in string at byte {position}"),
+            Self::PosOverflow => write!(f, "number too large to fit in target type"),
+            Self::NegOverflow => write!(f, "number too small to fit in target type"),
+        }
+    }
+}
+
+impl std::error::Error for ParseIntError {}
+
+/// [`FastScanner::scan`](crate::FastScanner::scan)のエラー。`E`はトークンの変換エラー
+#[derive(Debug)]
+pub enum ScanError<E> {
+    /// 読み込みに失敗した
+    Io(io::Error),
+    /// トークンが残っていない
+    Eof,
+    /// トークンを変換できない
+    Parse(E),
+}
+
+impl<E: fmt::Display> fmt::Display for ScanError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "failed to read input: {err}"),
+            Self::Eof => write!(f, "unexpected end of input"),
+            Self::Parse(err) => write!(f, "failed to parse token: {err}"),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display + 'static> std::error::Error for ScanError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Eof | Self::Parse(_) => None,
+        }
+    }
+}
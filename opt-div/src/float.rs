@@ -0,0 +1,456 @@
+//! 浮動小数点数の変換 (Eisel–Lemire)
+//!
+//! 有効数字19桁以下の10進表記を高速に変換する。正確に丸められないときや、`inf`/`nan`、
+//! 有効数字が多すぎるときは標準ライブラリの`str::parse`で変換する。
+
+mod table;
+
+use std::num::ParseFloatError;
+
+use crate::{combine8, is_digits8, FromStrFast, ParsePrefix};
+use table::{LARGEST_POWER_OF_FIVE, POWER_OF_FIVE_128, SMALLEST_POWER_OF_FIVE};
+
+/// `f32`と`f64`の共通部分
+trait Float: Copy {
+    const MANTISSA_EXPLICIT_BITS: u32;
+    const MINIMUM_EXPONENT: i32;
+    const INFINITE_POWER: i32;
+    const SMALLEST_POWER_OF_TEN: i64;
+    const LARGEST_POWER_OF_TEN: i64;
+    const MIN_EXPONENT_ROUND_TO_EVEN: i64;
+    const MAX_EXPONENT_ROUND_TO_EVEN: i64;
+    /// 仮数と`10^e`がどちらも正確に表せる範囲
+    const MAX_EXPONENT_FAST_PATH: i64;
+    const MAX_MANTISSA_FAST_PATH: u64;
+
+    /// 仮数と指数のどちらも正確に表せるときの`mantissa * 10^exp`
+    fn fast_path(mantissa: u64, exp: i64) -> Self;
+    fn from_parts(negative: bool, mantissa: u64, biased_exp: i32) -> Self;
+    fn neg(self) -> Self;
+}
+
+impl Float for f64 {
+    const MANTISSA_EXPLICIT_BITS: u32 = 52;
+    const MINIMUM_EXPONENT: i32 = -1023;
+    const INFINITE_POWER: i32 = 0x7ff;
+    const SMALLEST_POWER_OF_TEN: i64 = -342;
+    const LARGEST_POWER_OF_TEN: i64 = 308;
+    const MIN_EXPONENT_ROUND_TO_EVEN: i64 = -4;
+    const MAX_EXPONENT_ROUND_TO_EVEN: i64 = 23;
+    const MAX_EXPONENT_FAST_PATH: i64 = 22;
+    const MAX_MANTISSA_FAST_PATH: u64 = 2 << 52;
+
+    fn fast_path(mantissa: u64, exp: i64) -> Self {
+        const POW10: [f64; 23] = [
+            1e0, 1e1, 1e2, 1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9, 1e10, 1e11, 1e12, 1e13, 1e14, 1e15, 1e16, 1e17, 1e18,
+            1e19, 1e20, 1e21, 1e22,
+        ];
+        let pow = POW10[exp.unsigned_abs() as usize];
+        if exp < 0 {
+            mantissa as f64 / pow
+        } else {
+            mantissa as f64 * pow
+        }
+    }
+
+    fn from_parts(negative: bool, mantissa: u64, biased_exp: i32) -> Self {
+        f64::from_bits((negative as u64) << 63 | (biased_exp as u64) << 52 | mantissa)
+    }
+
+    fn neg(self) -> Self {
+        -self
+    }
+}
+
+impl Float for f32 {
+    const MANTISSA_EXPLICIT_BITS: u32 = 23;
+    const MINIMUM_EXPONENT: i32 = -127;
+    const INFINITE_POWER: i32 = 0xff;
+    const SMALLEST_POWER_OF_TEN: i64 = -65;
+    const LARGEST_POWER_OF_TEN: i64 = 38;
+    const MIN_EXPONENT_ROUND_TO_EVEN: i64 = -17;
+    const MAX_EXPONENT_ROUND_TO_EVEN: i64 = 10;
+    const MAX_EXPONENT_FAST_PATH: i64 = 10;
+    const MAX_MANTISSA_FAST_PATH: u64 = 2 << 23;
+
+    fn fast_path(mantissa: u64, exp: i64) -> Self {
+        const POW10: [f32; 11] = [1e0, 1e1, 1e2, 1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9, 1e10];
+        let pow = POW10[exp.unsigned_abs() as usize];
+        if exp < 0 {
+            mantissa as f32 / pow
+        } else {
+            mantissa as f32 * pow
+        }
+    }
+
+    fn from_parts(negative: bool, mantissa: u64, biased_exp: i32) -> Self {
+        f32::from_bits((negative as u32) << 31 | (biased_exp as u32) << 23 | mantissa as u32)
+    }
+
+    fn neg(self) -> Self {
+        -self
+    }
+}
+
+/// `±mantissa * 10^exp`の形の10進数
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Decimal {
+    negative: bool,
+    mantissa: u64,
+    exp: i64,
+}
+
+/// 有効数字の上限。これ以下なら`u64`に収まる
+const MAX_DIGITS: usize = 19;
+
+/// `[+-]digits[.digits][(e|E)[+-]digits]`を読む
+///
+/// 形式が違うときや、有効数字が19桁を超えるときは`None`
+fn parse_decimal(s: &[u8]) -> Option<Decimal> {
+    let (negative, mut s) = match s {
+        [b'-', s @ ..] => (true, s),
+        [b'+', s @ ..] => (false, s),
+        s => (false, s),
+    };
+    let mut mantissa = 0u64;
+    let mut digits = 0;
+    let mut exp = 0i64;
+    let mut any_digit = false;
+
+    // 整数部。先頭の0は有効数字に数えない
+    while let [b'0', rest @ ..] = s {
+        s = rest;
+        any_digit = true;
+    }
+    let before = s.len();
+    read_digits(&mut s, &mut mantissa, &mut digits)?;
+    any_digit |= s.len() != before;
+
+    // 小数部
+    if let [b'.', rest @ ..] = s {
+        s = rest;
+        if mantissa == 0 {
+            while let [b'0', rest @ ..] = s {
+                s = rest;
+                exp -= 1;
+                any_digit = true;
+            }
+        }
+        let before = s.len();
+        let before_digits = digits;
+        read_digits(&mut s, &mut mantissa, &mut digits)?;
+        any_digit |= s.len() != before;
+        exp -= (digits - before_digits) as i64;
+    }
+    if !any_digit {
+        return None;
+    }
+
+    // 指数部
+    if let [b'e' | b'E', rest @ ..] = s {
+        let (exp_negative, rest) = match rest {
+            [b'-', rest @ ..] => (true, rest),
+            [b'+', rest @ ..] => (false, rest),
+            rest => (false, rest),
+        };
+        if rest.is_empty() {
+            return None;
+        }
+        let mut e = 0i64;
+        for &c in rest {
+            if !c.is_ascii_digit() {
+                return None;
+            }
+            e = e * 10 + (c - b'0') as i64;
+            // 標準ライブラリは大きな指数を飽和させてから小数部の先頭の0の分と足すので、
+            // 結果を合わせるために任せる
+            if e >= 0x10000 {
+                return None;
+            }
+        }
+        exp += if exp_negative { -e } else { e };
+        s = &[];
+    }
+    if !s.is_empty() {
+        return None;
+    }
+    if mantissa == 0 {
+        exp = 0;
+    }
+    Some(Decimal { negative, mantissa, exp })
+}
+
+/// 数字を有効数字19桁まで`mantissa`に読み込む。19桁を超えるときは`None`
+#[inline]
+fn read_digits(s: &mut &[u8], mantissa: &mut u64, digits: &mut usize) -> Option<()> {
+    while let Some((chunk, rest)) = s.split_first_chunk::<8>() {
+        let n = u64::from_le_bytes(*chunk);
+        if !is_digits8(n) || *digits + 8 > MAX_DIGITS {
+            break;
+        }
+        *mantissa = *mantissa * 100_000_000 + combine8(n);
+        *digits += 8;
+        *s = rest;
+    }
+    while let [c @ b'0'..=b'9', rest @ ..] = *s {
+        if *digits == MAX_DIGITS {
+            return None;
+        }
+        *mantissa = *mantissa * 10 + (c - b'0') as u64;
+        *digits += 1;
+        *s = rest;
+    }
+    Some(())
+}
+
+/// `floor(log2(10^q)) + 63`
+fn power(q: i32) -> i32 {
+    (q.wrapping_mul(152_170 + 65536) >> 16) + 63
+}
+
+/// `w * 5^q`の上位128bitの近似。下位`precision`bitより下は不正確になりうる
+fn compute_product_approx(q: i64, w: u64, precision: u32) -> (u64, u64) {
+    let mask = u64::MAX >> precision;
+    let (hi5, lo5) = POWER_OF_FIVE_128[(q - SMALLEST_POWER_OF_FIVE) as usize];
+    let first = w as u128 * hi5 as u128;
+    let (mut first_lo, mut first_hi) = (first as u64, (first >> 64) as u64);
+    if first_hi & mask == mask {
+        let second_hi = ((w as u128 * lo5 as u128) >> 64) as u64;
+        first_lo = first_lo.wrapping_add(second_hi);
+        if second_hi > first_lo {
+            first_hi += 1;
+        }
+    }
+    (first_lo, first_hi)
+}
+
+/// Eisel–Lemireのアルゴリズムで`w * 10^q`を正しく丸めた`(仮数, バイアス付き指数)`を求める
+///
+/// 128bitの近似で丸め方向が決まらないときは`None`
+fn eisel_lemire<F: Float>(q: i64, mut w: u64) -> Option<(u64, i32)> {
+    if w == 0 || q < F::SMALLEST_POWER_OF_TEN {
+        return Some((0, 0));
+    }
+    if q > F::LARGEST_POWER_OF_TEN {
+        return Some((0, F::INFINITE_POWER));
+    }
+    debug_assert!((SMALLEST_POWER_OF_FIVE..=LARGEST_POWER_OF_FIVE).contains(&q));
+    let lz = w.leading_zeros();
+    w <<= lz;
+    let (lo, hi) = compute_product_approx(q, w, F::MANTISSA_EXPLICIT_BITS + 3);
+    if lo == u64::MAX && !(-27..=55).contains(&q) {
+        return None;
+    }
+    let upperbit = (hi >> 63) as i32;
+    let shift = upperbit + 64 - F::MANTISSA_EXPLICIT_BITS as i32 - 3;
+    let mut mantissa = hi >> shift;
+    let mut power2 = power(q as i32) + upperbit - lz as i32 - F::MINIMUM_EXPONENT;
+    if power2 <= 0 {
+        // 非正規化数
+        if -power2 + 1 >= 64 {
+            return Some((0, 0));
+        }
+        mantissa >>= -power2 + 1;
+        mantissa += mantissa & 1;
+        mantissa >>= 1;
+        power2 = (mantissa >= 1 << F::MANTISSA_EXPLICIT_BITS) as i32;
+        return Some((mantissa, power2));
+    }
+    // ちょうど中間のときは偶数に丸める
+    if lo <= 1
+        && (F::MIN_EXPONENT_ROUND_TO_EVEN..=F::MAX_EXPONENT_ROUND_TO_EVEN).contains(&q)
+        && mantissa & 3 == 1
+        && mantissa << shift == hi
+    {
+        mantissa &= !1;
+    }
+    mantissa += mantissa & 1;
+    mantissa >>= 1;
+    if mantissa >= 2 << F::MANTISSA_EXPLICIT_BITS {
+        mantissa = 1 << F::MANTISSA_EXPLICIT_BITS;
+        power2 += 1;
+    }
+    mantissa &= !(1 << F::MANTISSA_EXPLICIT_BITS);
+    if power2 >= F::INFINITE_POWER {
+        return Some((0, F::INFINITE_POWER));
+    }
+    Some((mantissa, power2))
+}
+
+fn parse_float<F: Float>(s: &[u8]) -> Option<F> {
+    let Decimal { negative, mantissa, exp } = parse_decimal(s)?;
+    if mantissa <= F::MAX_MANTISSA_FAST_PATH && (-F::MAX_EXPONENT_FAST_PATH..=F::MAX_EXPONENT_FAST_PATH).contains(&exp) {
+        let value = F::fast_path(mantissa, exp);
+        return Some(if negative { value.neg() } else { value });
+    }
+    let (mantissa, biased_exp) = eisel_lemire::<F>(exp, mantissa)?;
+    Some(F::from_parts(negative, mantissa, biased_exp))
+}
+
+/// `str::parse`が受け付ける形の先頭部分の長さ。なければ0
+fn prefix_len(s: &[u8]) -> usize {
+    let digits = |s: &[u8], i: usize| s[i..].iter().take_while(|c| c.is_ascii_digit()).count();
+    let sign = matches!(s.first(), Some(b'+' | b'-')) as usize;
+    let rest = &s[sign..];
+    for word in [&b"infinity"[..], b"inf", b"nan"] {
+        if rest.get(..word.len()).is_some_and(|w| w.eq_ignore_ascii_case(word)) {
+            return sign + word.len();
+        }
+    }
+    let int = digits(s, sign);
+    let mut len = sign + int;
+    let mut frac = 0;
+    if s.get(len) == Some(&b'.') {
+        frac = digits(s, len + 1);
+        len += 1 + frac;
+    }
+    if int + frac == 0 {
+        return 0;
+    }
+    if let Some(b'e' | b'E') = s.get(len) {
+        let exp_sign = matches!(s.get(len + 1), Some(b'+' | b'-')) as usize;
+        let exp = digits(s, len + 1 + exp_sign);
+        if exp > 0 {
+            len += 1 + exp_sign + exp;
+        }
+    }
+    len
+}
+
+macro_rules! impl_float {
+    ($($t:ty),*) => {
+        $(
+            impl FromStrFast for $t {
+                type Err = ParseFloatError;
+                fn parse_fast(s: &str) -> Result<Self, Self::Err> {
+                    match parse_float::<$t>(s.as_bytes()) {
+                        Some(value) => Ok(value),
+                        None => s.parse(),
+                    }
+                }
+
+                /// UTF-8でないバイト列は置換文字に直してから標準ライブラリでエラーにする
+                fn parse_fast_bytes(s: &[u8]) -> Result<Self, Self::Err> {
+                    match parse_float::<$t>(s) {
+                        Some(value) => Ok(value),
+                        None => String::from_utf8_lossy(s).parse(),
+                    }
+                }
+            }
+
+            impl ParsePrefix for $t {
+                fn parse_prefix(s: &[u8]) -> Result<(Self, usize), Self::Err> {
+                    match prefix_len(s) {
+                        0 => Err(Self::parse_fast_bytes(&s[..s.len().min(1)]).unwrap_err()),
+                        len => Self::parse_fast_bytes(&s[..len]).map(|value| (value, len)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_float!(f32, f64);
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+
+    fn check(s: &str) {
+        let expected = s.parse::<f64>().map(f64::to_bits);
+        assert_eq!(f64::parse_fast(s).map(f64::to_bits), expected, "{s:?} as f64");
+        assert_eq!(f64::parse_fast_bytes(s.as_bytes()).map(f64::to_bits), expected, "{s:?} as f64");
+        if let Ok(expected) = expected {
+            let prefix = f64::parse_prefix(format!("{s} 1").as_bytes()).map(|(x, len)| (x.to_bits(), len));
+            assert_eq!(prefix, Ok((expected, s.len())), "{s:?}");
+        }
+        let expected = s.parse::<f32>().map(f32::to_bits);
+        assert_eq!(f32::parse_fast(s).map(f32::to_bits), expected, "{s:?} as f32");
+    }
+
+    #[test]
+    fn edge_cases() {
+        let cases = [
+            "0", "-0", "+0", "0.0", "-0.0", "1", "1.", ".5", "+.5", "-.5", "00012.3400", "0.000123", "1e23", "1E-5",
+            "1e+5", "9007199254740993", "9007199254740992.5", "2.2250738585072011e-308", "2.2250738585072014e-308",
+            "4.9406564584124654e-324", "2.4703282292062327e-324", "2.4703282292062328e-324", "1.7976931348623157e308",
+            "1.7976931348623158e308", "1.7976931348623159e308", "1e309", "1e-400", "1e400", "0e999999999999",
+            "1e-999999999999", "3.4028235e38", "3.4028236e38", "1.4e-45", "7e-46", "1.17549435e-38", "16777217",
+            "0.1", "0.2", "0.3", "123456789012345678", "1234567890123456789", "12345678901234567890",
+            "0.12345678901234567890", "1.0000000000000000000000000001", "inf", "-Infinity", "NaN", "", ".", "-",
+            "e5", "1e", "1e+", "1.2.3", "1x", " 1", "1 ", "--1", "0x10",
+        ];
+        for s in cases {
+            check(s);
+        }
+        // 小数部の先頭の0で下げた指数を、大きな指数で打ち消す
+        let zeros = "0".repeat(100_000);
+        for s in [
+            format!("0.{zeros}1e100000"),
+            format!("-0.{zeros}15e100001"),
+            format!("0.{zeros}1e65535"),
+            format!("0.{zeros}1e99999999999999999999"),
+            format!("{zeros}1e-99999999999999999999"),
+        ] {
+            check(&s);
+        }
+        assert!(f64::parse_fast("nan").unwrap().is_nan());
+    }
+
+    #[test]
+    fn prefix() {
+        let cases: [(&str, Option<(f64, usize)>); 13] = [
+            ("1.5e3x", Some((1500.0, 5))),
+            ("1e", Some((1.0, 1))),
+            ("1e+", Some((1.0, 1))),
+            ("2E-1,", Some((0.2, 4))),
+            ("-inf,", Some((f64::NEG_INFINITY, 4))),
+            ("Infinityx", Some((f64::INFINITY, 8))),
+            (".5.", Some((0.5, 2))),
+            ("1.e2", Some((100.0, 4))),
+            ("-0.", Some((-0.0, 3))),
+            (".", None),
+            ("e5", None),
+            ("+", None),
+            ("", None),
+        ];
+        for (s, expected) in cases {
+            let actual = f64::parse_prefix(s.as_bytes()).ok().map(|(x, len)| (x.to_bits(), len));
+            assert_eq!(actual, expected.map(|(x, len)| (x.to_bits(), len)), "{s:?}");
+        }
+        assert!(f64::parse_prefix(b"nan").unwrap().0.is_nan());
+        assert_eq!(f64::parse_prefix(b"x"), Err("x".parse::<f64>().unwrap_err()));
+        assert_eq!(f32::parse_fast_bytes(b"1\xff"), Err("1x".parse::<f32>().unwrap_err()));
+    }
+
+    #[test]
+    fn random_bits() {
+        let mut rng = StdRng::seed_from_u64(43);
+        for _ in 0..200_000 {
+            let x = f64::from_bits(rng.gen());
+            if x.is_finite() {
+                check(&x.to_string());
+                check(&format!("{x:e}"));
+            }
+            let y = f32::from_bits(rng.gen());
+            if y.is_finite() {
+                check(&y.to_string());
+                check(&format!("{y:e}"));
+            }
+        }
+    }
+
+    #[test]
+    fn random_decimals() {
+        let mut rng = StdRng::seed_from_u64(44);
+        for _ in 0..200_000 {
+            let digits = (0..rng.gen_range(1..=22)).map(|_| rng.gen_range(b'0'..=b'9') as char).collect::<String>();
+            let point = rng.gen_range(0..=digits.len());
+            let s = format!("{}.{}e{}", &digits[..point], &digits[point..], rng.gen_range(-350..=320));
+            check(&s);
+            check(&digits);
+        }
+    }
+}
@@ -0,0 +1,178 @@
+//! 整数の10進表記への変換
+//!
+//! 下位から2桁ずつ表引きして、バッファの末尾から書く。
+
+use std::io::{self, Write};
+
+/// `"00" ..= "99"`
+const PAIRS: [u8; 200] = {
+    let mut table = [0; 200];
+    let mut i = 0;
+    while i < 100 {
+        table[2 * i] = b'0' + (i / 10) as u8;
+        table[2 * i + 1] = b'0' + (i % 10) as u8;
+        i += 1;
+    }
+    table
+};
+
+pub trait ToStrFast: Copy {
+    /// 10進表記の最大バイト数
+    const MAX_LEN: usize;
+
+    /// `buf`の末尾に10進表記を書き、書いた部分を返す
+    ///
+    /// `N < Self::MAX_LEN`のときはコンパイルエラーになる。
+    fn format_fast<const N: usize>(self, buf: &mut [u8; N]) -> &str;
+
+    fn write_fast<W: Write + ?Sized>(self, w: &mut W) -> io::Result<()> {
+        let mut buf = [0; 40];
+        w.write_all(self.format_fast(&mut buf).as_bytes())
+    }
+}
+
+#[inline]
+fn write_pair(buf: &mut [u8], pos: usize, n: usize) {
+    buf[pos..pos + 2].copy_from_slice(&PAIRS[2 * n..2 * n + 2]);
+}
+
+/// `buf`の末尾に`n`を書き、書き始めの位置を返す
+#[inline]
+fn write_u64(mut n: u64, buf: &mut [u8]) -> usize {
+    let mut pos = buf.len();
+    while n >= 10_000 {
+        let rem = (n % 10_000) as usize;
+        n /= 10_000;
+        pos -= 4;
+        write_pair(buf, pos, rem / 100);
+        write_pair(buf, pos + 2, rem % 100);
+    }
+    let mut n = n as usize;
+    if n >= 100 {
+        pos -= 2;
+        write_pair(buf, pos, n % 100);
+        n /= 100;
+    }
+    if n >= 10 {
+        pos -= 2;
+        write_pair(buf, pos, n);
+    } else {
+        pos -= 1;
+        buf[pos] = b'0' + n as u8;
+    }
+    pos
+}
+
+fn write_u128(mut n: u128, buf: &mut [u8]) -> usize {
+    const CHUNK: u128 = 10_000_000_000_000_000_000;
+    let mut end = buf.len();
+    // u64に収まるまで下位19桁ずつ0埋めして書く
+    while n > u64::MAX as u128 {
+        let start = write_u64((n % CHUNK) as u64, &mut buf[..end]);
+        buf[end - 19..start].fill(b'0');
+        n /= CHUNK;
+        end -= 19;
+    }
+    write_u64(n as u64, &mut buf[..end])
+}
+
+macro_rules! impl_unsigned {
+    ($($t:ty => $write:ident),*) => {
+        $(
+            impl ToStrFast for $t {
+                const MAX_LEN: usize = <$t>::MAX.ilog10() as usize + 1;
+
+                fn format_fast<const N: usize>(self, buf: &mut [u8; N]) -> &str {
+                    const { assert!(N >= <$t>::MAX_LEN, "buffer is too short") };
+                    let pos = $write(self as _, buf);
+                    // SAFETY: ASCIIの数字だけを書いた
+                    unsafe { std::str::from_utf8_unchecked(&buf[pos..]) }
+                }
+            }
+        )*
+    };
+}
+
+impl_unsigned!(u8 => write_u64, u16 => write_u64, u32 => write_u64, u64 => write_u64, usize => write_u64, u128 => write_u128);
+
+macro_rules! impl_signed {
+    ($($t:ty => $write:ident),*) => {
+        $(
+            impl ToStrFast for $t {
+                const MAX_LEN: usize = <$t>::MIN.unsigned_abs().ilog10() as usize + 2;
+
+                fn format_fast<const N: usize>(self, buf: &mut [u8; N]) -> &str {
+                    const { assert!(N >= <$t>::MAX_LEN, "buffer is too short") };
+                    let mut pos = $write(self.unsigned_abs() as _, buf);
+                    if self < 0 {
+                        pos -= 1;
+                        buf[pos] = b'-';
+                    }
+                    // SAFETY: ASCIIの数字と'-'だけを書いた
+                    unsafe { std::str::from_utf8_unchecked(&buf[pos..]) }
+                }
+            }
+        )*
+    };
+}
+
+impl_signed!(i8 => write_u64, i16 => write_u64, i32 => write_u64, i64 => write_u64, isize => write_u64, i128 => write_u128);
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+
+    fn check<T: ToStrFast + ToString>(n: T) {
+        let mut buf = [0; 40];
+        assert_eq!(n.format_fast(&mut buf), n.to_string());
+        let mut out = Vec::new();
+        n.write_fast(&mut out).unwrap();
+        assert_eq!(out, n.to_string().as_bytes());
+        assert!(n.to_string().len() <= T::MAX_LEN);
+    }
+
+    #[test]
+    fn boundaries() {
+        for k in 0..=38 {
+            let p = 10u128.pow(k);
+            for n in [p - 1, p, p + 1] {
+                check(n);
+                check(n as u64);
+                check(n as u32);
+                check(n as i128);
+                check(-(n as i128));
+                check(n as i64);
+                check((n as i64).wrapping_neg());
+            }
+        }
+        check(u128::MAX);
+        check(i128::MIN);
+        check(i128::MAX);
+        check(i64::MIN);
+        check(u8::MAX);
+        check(i8::MIN);
+        check(i16::MIN);
+        check(usize::MAX);
+        check(isize::MIN);
+        assert_eq!((u8::MAX_LEN, i8::MAX_LEN, u64::MAX_LEN, i128::MAX_LEN), (3, 4, 20, 40));
+        // 最大桁数ちょうどのバッファ
+        let mut buf = [0; 11];
+        assert_eq!(i32::MIN.format_fast(&mut buf), "-2147483648");
+    }
+
+    #[test]
+    fn random() {
+        let mut rng = StdRng::seed_from_u64(44);
+        for _ in 0..100_000 {
+            let n = rng.gen::<u128>() >> rng.gen_range(0..128);
+            check(n);
+            check(n as u64);
+            check(n as i64);
+            check(n as i32);
+            check(n as i128);
+            check(n as i16);
+        }
+    }
+}
@@ -1,6 +1,38 @@
+mod divisor;
+pub mod error;
+mod float;
+mod format;
+mod radix;
+pub mod scanner;
+mod simd;
+
+pub use divisor::{Divisor, DivisorInt};
+pub use error::{ParseIntError, ScanError};
+pub use format::ToStrFast;
+pub use radix::FromStrRadixFast;
+pub use scanner::FastScanner;
+pub use simd::parse_u64_many;
+
+/// ベンチマークからSIMDとSWARの実装を直接比べるための入口。安定したAPIではない
+#[doc(hidden)]
+pub mod __bench {
+    pub use crate::simd::{parse16, parse16_swar};
+}
+
 pub trait FromStrFast: Sized {
     type Err;
-    fn parse_fast(s: &str) -> Result<Self, Self::Err>;
+    #[inline]
+    fn parse_fast(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_fast_bytes(s.as_bytes())
+    }
+
+    /// `mmap`した入力などをUTF-8の検証なしで変換する。結果は`parse_fast`と同じ
+    fn parse_fast_bytes(s: &[u8]) -> Result<Self, Self::Err>;
+
+    /// 後ろの余白を読めることを使って、短い数字列もコピーせずに変換する。結果は`parse_fast`と同じ
+    fn parse_fast_padded(s: PaddedBytes<'_>) -> Result<Self, Self::Err> {
+        Self::parse_fast_bytes(s.as_bytes())
+    }
 
     /// # Safety
     /// 変換に失敗しないこと
@@ -9,19 +41,98 @@ pub trait FromStrFast: Sized {
     }
 }
 
+/// 入力の後ろに[`PADDING`](Self::PADDING)バイト以上の読める領域がある数字列
+///
+/// 8桁未満の数字列も8バイトまとめて読み、余分なバイトは捨てる。余白の中身は結果に影響しない。
+#[derive(Clone, Copy, Debug)]
+pub struct PaddedBytes<'a> {
+    buf: &'a [u8],
+    len: usize,
+}
+
+impl<'a> PaddedBytes<'a> {
+    pub const PADDING: usize = 8;
+
+    /// `buf[..len]`を入力とする。`buf.len() < len + PADDING`のときは`None`
+    pub fn new(buf: &'a [u8], len: usize) -> Option<Self> {
+        (len.checked_add(Self::PADDING)? <= buf.len()).then_some(Self { buf, len })
+    }
+
+    pub fn as_bytes(&self) -> &'a [u8] {
+        &self.buf[..self.len]
+    }
+}
+
+/// 入力の続きを読む。`Err`は[`FromStrFast::parse_fast`]と同じ
+pub trait ParsePrefix: FromStrFast {
+    /// 先頭から読めるだけ読み、値と読んだバイト数を返す
+    ///
+    /// 値の形をした部分がないときは、その位置までと次の1バイトを変換したときのエラーになる。
+    fn parse_prefix(s: &[u8]) -> Result<(Self, usize), Self::Err>;
+}
+
+/// 先頭`N`個を取り出す。足りないときは前を`default`で埋める
 fn slice_as_sized<T: Copy, const N: usize>(slice: &[T], default: T) -> [T; N] {
-    if slice.len() < N {
-        let mut ret = [default; N];
-        ret[N - slice.len()..].copy_from_slice(slice);
-        ret
-    } else {
-        unsafe {
-            (slice.as_ptr() as *const [T; N]).read()
+    match slice.first_chunk() {
+        Some(chunk) => *chunk,
+        None => {
+            let mut ret = [default; N];
+            ret[N - slice.len()..].copy_from_slice(slice);
+            ret
         }
     }
 }
 
-fn parse8(s: &[u8]) -> u64 {
+/// 8バイト未満の`s`をリトルエンディアンで読む。上位の余ったバイトは0
+///
+/// 長さに合わせて重なる読み込みにすると、`memcpy`を呼ばずに済む。
+#[inline]
+fn load_short(s: &[u8]) -> u64 {
+    debug_assert!(s.len() < 8);
+    let len = s.len();
+    if len >= 4 {
+        let lo = u32::from_le_bytes(s[..4].try_into().unwrap()) as u64;
+        let hi = u32::from_le_bytes(s[len - 4..].try_into().unwrap()) as u64;
+        lo | hi << (8 * (len - 4))
+    } else if len > 0 {
+        // 1から3バイトは先頭、中央、末尾の3バイトで全て覆える
+        let mid = len / 2;
+        s[0] as u64 | (s[mid] as u64) << (8 * mid) | (s[len - 1] as u64) << (8 * (len - 1))
+    } else {
+        0
+    }
+}
+
+/// `buf[..len]` (`len <= 8`)を、足りない桁を'0'で埋めたリトルエンディアンの8バイトにする
+///
+/// `buf`が8バイト以上あれば後ろのバイトごと読んで捨てる。
+#[inline]
+fn load8(buf: &[u8], len: usize) -> u64 {
+    debug_assert!(len <= 8 && len <= buf.len());
+    if len == 0 {
+        return 0x3030_3030_3030_3030;
+    }
+    let n = match buf.first_chunk::<8>() {
+        Some(chunk) => u64::from_le_bytes(*chunk),
+        None => load_short(&buf[..len]),
+    };
+    let fill = 0x3030_3030_3030_3030u64.checked_shr(8 * len as u32).unwrap_or(0);
+    (n << (8 * (8 - len))) | fill
+}
+
+/// 8バイトが全てASCII数字か
+///
+/// `0x30..=0x39`の各バイトは上位ニブルが3で、6を足しても上位ニブルが3のまま。
+#[inline]
+fn is_digits8(n: u64) -> bool {
+    const HIGH: u64 = 0xf0f0_f0f0_f0f0_f0f0;
+    let carried = n.wrapping_add(0x0606_0606_0606_0606) & HIGH;
+    (n & HIGH) | (carried >> 4) == 0x3333_3333_3333_3333
+}
+
+/// リトルエンディアンで詰めた8桁を数値にする
+#[inline]
+fn combine8(mut n: u64) -> u64 {
     const MASK0: u64 = 0x0f0f_0f0f_0f0f_0f0f;
     const MUL1: u64 = (10 << 8) + 1;
     const MASK1: u64 = 0x00ff_00ff_00ff_00ff;
@@ -29,8 +140,6 @@ fn parse8(s: &[u8]) -> u64 {
     const MASK2: u64 = 0x0000_ffff_0000_ffff;
     const MUL3: u64 = (10000 << 32) + 1;
     const MASK3: u64 = 0x0000_0000_ffff_ffff;
-    let s = slice_as_sized::<_, 8>(s, 0);
-    let mut n = u64::from_le_bytes(s);
     n &= MASK0;
     n = (n.wrapping_mul(MUL1) >> 8) & MASK1;
     n = (n.wrapping_mul(MUL2) >> 16) & MASK2;
@@ -38,23 +147,391 @@ fn parse8(s: &[u8]) -> u64 {
     n
 }
 
-impl FromStrFast for u32 {
-    type Err = ();
+/// 8桁以下の数字列を読む。数字以外を含むときは`None`
+#[inline]
+fn parse8(s: &[u8]) -> Option<u64> {
+    // 足りない桁は'0'で埋める
+    let n = u64::from_le_bytes(slice_as_sized::<_, 8>(s, b'0'));
+    let value = combine8(n);
+    is_digits8(n).then_some(value)
+}
+
+/// [`load8`]と同じく`buf[..len]`を読む
+#[inline]
+fn parse8_head(buf: &[u8], len: usize) -> Option<u64> {
+    let n = load8(buf, len);
+    let value = combine8(n);
+    is_digits8(n).then_some(value)
+}
+
+macro_rules! parse_digits {
+    ($($name:ident, $unchecked:ident => $t:ty),*) => {
+        $(
+            /// 符号を除いた数字列`buf[..len]`を先頭から8桁ずつ読む。桁あふれしたら`None`
+            ///
+            /// `buf[len..]`は先頭の8桁未満を読むときに余白として使う。
+            #[inline]
+            fn $name(buf: &[u8], len: usize) -> Option<$t> {
+                /// 17桁以上のとき。短い入力で呼び出し側が大きくならないよう分けておく
+                #[inline(never)]
+                fn long(mut buf: &[u8], mut len: usize) -> Option<$t> {
+                    // 最大桁数を超えるときだけ先頭の0を読み飛ばす
+                    if len > <$t>::MAX.ilog10() as usize + 1 {
+                        let zeros = buf[..len].iter().position(|&c| c != b'0').unwrap_or(len);
+                        buf = &buf[zeros..];
+                        len -= zeros;
+                    }
+                    let head = match len % 8 {
+                        0 => len.min(8),
+                        r => r,
+                    };
+                    let mut n = parse8_head(buf, head)? as $t;
+                    let mut rest = &buf[head..len];
+                    while let Some((chunk, tail)) = rest.split_first_chunk::<16>() {
+                        n = n.checked_mul(10_000_000_000_000_000)?.checked_add(simd::parse16(chunk)? as $t)?;
+                        rest = tail;
+                    }
+                    for chunk in rest.chunks_exact(8) {
+                        n = n.checked_mul(100_000_000)?.checked_add(parse8(chunk)? as $t)?;
+                    }
+                    Some(n)
+                }
+
+                if len <= 8 {
+                    return parse8_head(buf, len).map(|n| n as $t);
+                }
+                if len <= 16 {
+                    // 16桁以下は`u64`でも桁あふれしない
+                    let head = len - 8;
+                    let high = parse8_head(buf, head)?;
+                    let low = parse8_head(&buf[head..], 8)?;
+                    return Some((high * 100_000_000 + low) as $t);
+                }
+                long(buf, len)
+            }
+
+            /// 数字だけからなり、桁あふれしないことが分かっている数字列を読む
+            fn $unchecked(buf: &[u8], len: usize) -> $t {
+                let head = match len % 8 {
+                    0 => len.min(8),
+                    r => r,
+                };
+                let mut n = combine8(load8(buf, head)) as $t;
+                for chunk in buf[head..len].chunks_exact(8) {
+                    n = n.wrapping_mul(100_000_000).wrapping_add(combine8(load8(chunk, 8)) as $t);
+                }
+                n
+            }
+        )*
+    };
+}
+
+parse_digits!(parse_u64, parse_u64_unchecked => u64, parse_u128, parse_u128_unchecked => u128);
+
+/// 高速な経路で読めなかった数字列`s`のエラーを、標準ライブラリと同じく先頭から順に調べて決める
+///
+/// `offset`は元の文字列での`s`の開始位置、`limit`は絶対値の上限。
+#[cold]
+fn digits_error(s: &[u8], offset: usize, limit: u128, negative: bool) -> ParseIntError {
+    match radix::parse_scalar(s, offset, 10, None, limit, negative) {
+        Err(err) => err,
+        Ok(_) => unreachable!("{:?} is a valid number", String::from_utf8_lossy(s)),
+    }
+}
+
+/// 数字列`buf[..len]`を読む。`buf[len..]`は余白
+trait ParseIntBuf: Sized {
+    fn parse_buf(buf: &[u8], len: usize) -> Result<Self, ParseIntError>;
+
+    /// 数字だけからなり、桁あふれしないことが分かっている符号付きの数字列を読む
+    fn parse_unchecked(s: &[u8]) -> Self;
+}
+
+/// 整数の形をした先頭部分を読む。`sign`は符号として読むバイト
+fn parse_int_prefix<T: ParseIntBuf>(s: &[u8], sign: &[u8]) -> Result<(T, usize), ParseIntError> {
+    let start = s.first().is_some_and(|c| sign.contains(c)) as usize;
+    let len = start + s[start..].iter().position(|c| !c.is_ascii_digit()).unwrap_or(s.len() - start);
+    if len == start {
+        return match T::parse_buf(s, (start + 1).min(s.len())) {
+            Err(err) => Err(err),
+            Ok(_) => unreachable!("no digits in {:?}", String::from_utf8_lossy(s)),
+        };
+    }
+    T::parse_buf(s, len).map(|n| (n, len))
+}
+
+macro_rules! impl_int {
+    ($($t:ty => $sign:expr),*) => {
+        $(
+            impl FromStrFast for $t {
+                type Err = ParseIntError;
+                #[inline]
+                fn parse_fast_bytes(s: &[u8]) -> Result<Self, Self::Err> {
+                    Self::parse_buf(s, s.len())
+                }
+
+                #[inline]
+                fn parse_fast_padded(s: PaddedBytes<'_>) -> Result<Self, Self::Err> {
+                    Self::parse_buf(s.buf, s.len)
+                }
+
+                unsafe fn parse_fast_unchecked(s: &str) -> Self {
+                    Self::parse_unchecked(s.as_bytes())
+                }
+            }
+
+            impl ParsePrefix for $t {
+                fn parse_prefix(s: &[u8]) -> Result<(Self, usize), Self::Err> {
+                    parse_int_prefix(s, $sign)
+                }
+            }
+        )*
+    };
+}
+
+impl_int!(u8 => b"+", u16 => b"+", u32 => b"+", u64 => b"+", usize => b"+", u128 => b"+");
+impl_int!(i8 => b"+-", i16 => b"+-", i32 => b"+-", i64 => b"+-", isize => b"+-", i128 => b"+-");
+
+macro_rules! impl_unsigned {
+    ($($t:ty => $parse:ident, $unchecked:ident),*) => {
+        $(
+            impl ParseIntBuf for $t {
+                #[allow(clippy::unnecessary_fallible_conversions)]
+                #[inline(always)]
+                fn parse_buf(buf: &[u8], len: usize) -> Result<Self, ParseIntError> {
+                    let offset = match &buf[..len] {
+                        [b'0'..=b'9', ..] => 0,
+                        [] => return Err(ParseIntError::Empty),
+                        // 符号なし整数では`-`自体が不正な文字
+                        [b'+'] | [b'-', ..] => return Err(ParseIntError::InvalidDigit { position: 0 }),
+                        [b'+', ..] => 1,
+                        _ => 0,
+                    };
+                    let n = $parse(&buf[offset..], len - offset)
+                        .ok_or_else(|| digits_error(&buf[offset..len], offset, <$t>::MAX as u128, false))?;
+                    <$t>::try_from(n).map_err(|_| ParseIntError::PosOverflow)
+                }
+
+                fn parse_unchecked(s: &[u8]) -> Self {
+                    let s = s.strip_prefix(b"+").unwrap_or(s);
+                    $unchecked(s, s.len()) as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_unsigned!(
+    u8 => parse_u64, parse_u64_unchecked,
+    u16 => parse_u64, parse_u64_unchecked,
+    u32 => parse_u64, parse_u64_unchecked,
+    u64 => parse_u64, parse_u64_unchecked,
+    usize => parse_u64, parse_u64_unchecked,
+    u128 => parse_u128, parse_u128_unchecked
+);
+
+macro_rules! impl_signed {
+    ($($t:ty => $parse:ident, $unchecked:ident),*) => {
+        $(
+            impl ParseIntBuf for $t {
+                #[inline(always)]
+                fn parse_buf(buf: &[u8], len: usize) -> Result<Self, ParseIntError> {
+                    // 符号はランダムなことも多いので、符号の有無で分岐させない
+                    let Some(&first) = buf[..len].first() else {
+                        return Err(ParseIntError::Empty);
+                    };
+                    let negative = first == b'-';
+                    let offset = (negative | (first == b'+')) as usize;
+                    if len == offset {
+                        return Err(ParseIntError::InvalidDigit { position: 0 });
+                    }
+                    let n = $parse(&buf[offset..], len - offset).ok_or_else(|| {
+                        let limit = if negative { <$t>::MIN.unsigned_abs() as u128 } else { <$t>::MAX as u128 };
+                        digits_error(&buf[offset..len], offset, limit, negative)
+                    })?;
+                    // |MIN| = MAX + 1 なので絶対値で比べてから符号を反転する
+                    if n > (<$t>::MAX as u128 + negative as u128) as _ {
+                        return Err(if negative { ParseIntError::NegOverflow } else { ParseIntError::PosOverflow });
+                    }
+                    let mask = (negative as $t).wrapping_neg();
+                    Ok(((n as $t) ^ mask).wrapping_sub(mask))
+                }
+
+                fn parse_unchecked(s: &[u8]) -> Self {
+                    match s {
+                        [b'-', s @ ..] => ($unchecked(s, s.len()) as $t).wrapping_neg(),
+                        [b'+', s @ ..] | s => $unchecked(s, s.len()) as $t,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_signed!(
+    i8 => parse_u64, parse_u64_unchecked,
+    i16 => parse_u64, parse_u64_unchecked,
+    i32 => parse_u64, parse_u64_unchecked,
+    i64 => parse_u64, parse_u64_unchecked,
+    isize => parse_u64, parse_u64_unchecked,
+    i128 => parse_u128, parse_u128_unchecked
+);
+
+impl FromStrFast for String {
+    type Err = std::str::Utf8Error;
     fn parse_fast(s: &str) -> Result<Self, Self::Err> {
-        let mut s = s.as_bytes();
-        if s.first().copied().ok_or(())? == b'+' {
-            s = &s[1..];
+        Ok(s.to_owned())
+    }
+
+    fn parse_fast_bytes(s: &[u8]) -> Result<Self, Self::Err> {
+        std::str::from_utf8(s).map(str::to_owned)
+    }
+}
+
+/// 範囲外を読んでいないことはMiriでも確かめる。全部では遅いので入口と余白を使うテストに絞る
+///
+/// ```text
+/// cargo +nightly miri test -p opt-div --lib -- tests::prefix tests::signs_and_zeros scanner::tests::buffer_boundaries
+/// ```
+#[cfg(test)]
+mod tests {
+    use std::{fmt::Debug, str::FromStr};
+
+    use super::*;
+
+    fn check<T>(s: &str)
+    where
+        T: ParsePrefix<Err = ParseIntError> + FromStr<Err = std::num::ParseIntError> + PartialEq + Debug,
+    {
+        let signed = "-1".parse::<T>().is_ok();
+        let expected = s.parse::<T>().map_err(|e| ParseIntError::from_std(&e, s, signed).unwrap());
+        assert_eq!(T::parse_fast(s), expected, "{s:?} as {}", std::any::type_name::<T>());
+        assert_eq!(T::parse_fast_bytes(s.as_bytes()), expected, "{s:?}");
+        // 余白の中身は結果に影響しない
+        for padding in [b'0', b'9', b' ', 0xff] {
+            let mut buf = s.as_bytes().to_vec();
+            buf.resize(s.len() + PaddedBytes::PADDING, padding);
+            assert_eq!(T::parse_fast_padded(PaddedBytes::new(&buf, s.len()).unwrap()), expected, "{s:?}");
+        }
+        if let Ok(expected) = expected {
+            assert_eq!(unsafe { T::parse_fast_unchecked(s) }, expected, "{s:?}");
+            assert_eq!(T::parse_prefix(format!("{s} 7").as_bytes()), Ok((expected, s.len())), "{s:?}");
+        }
+    }
+
+    fn check_all(s: &str) {
+        check::<u8>(s);
+        check::<u16>(s);
+        check::<u32>(s);
+        check::<u64>(s);
+        check::<u128>(s);
+        check::<usize>(s);
+        check::<i8>(s);
+        check::<i16>(s);
+        check::<i32>(s);
+        check::<i64>(s);
+        check::<i128>(s);
+        check::<isize>(s);
+    }
+
+    #[test]
+    fn boundaries() {
+        let bounds = [
+            u8::MAX as i128,
+            u16::MAX as i128,
+            u32::MAX as i128,
+            u64::MAX as i128,
+            i8::MIN as i128,
+            i16::MIN as i128,
+            i32::MIN as i128,
+            i64::MIN as i128,
+            i128::MIN,
+            i128::MAX,
+        ];
+        for b in bounds {
+            for d in -2..=2 {
+                let Some(n) = b.checked_add(d) else {
+                    continue;
+                };
+                check_all(&n.to_string());
+                if n >= 0 {
+                    check_all(&format!("+{n}"));
+                }
+            }
+        }
+        check_all(&u128::MAX.to_string());
+        check_all(&(u128::MAX / 10 + 1).to_string());
+        check_all(&format!("{}0", u128::MAX));
+    }
+
+    #[test]
+    fn signs_and_zeros() {
+        for s in ["", "+", "-", "0", "-0", "+0", "00000000000000000000000000000000000000000042", "-0000000000000000000000128"] {
+            check_all(s);
         }
-        if s.len() <= 8 {
-            let n = parse8(s) as u32;
-            return Ok(n);
+        // '0'-'9'の前後のバイトや、マスクすると数字になるバイト
+        for c in ["/", ":", "a", " ", "\0", "\u{10}", "p", "\u{7f}", "é", "٣"] {
+            for s in [c.to_string(), format!("{c}1"), format!("1{c}"), format!("12345678{c}"), format!("123456789012{c}34")] {
+                check_all(&s);
+                check_all(&format!("-{s}"));
+            }
         }
-        let (s, rest) = s.split_at(s.len() - 8);
-        let n = parse8(rest) as u32;
-        if s.len() <= 8 {
-            n.checked_add((parse8(s) as u32).checked_mul(100_000_000).ok_or(())?).ok_or(())
-        } else {
-            Err(())
+        check_all("12a4");
+        assert_eq!(u32::parse_fast("12a4"), Err(ParseIntError::InvalidDigit { position: 2 }));
+        assert_eq!(i64::parse_fast("-"), Err(ParseIntError::InvalidDigit { position: 0 }));
+        // 符号なし整数の`-`は後ろに数字でないバイトがあっても符号の位置
+        assert_eq!(u32::parse_fast("-12a4"), Err(ParseIntError::InvalidDigit { position: 0 }));
+        assert_eq!(i32::parse_fast("-12a4"), Err(ParseIntError::InvalidDigit { position: 3 }));
+        let err = "-12a4".parse::<u32>().unwrap_err();
+        assert_eq!(ParseIntError::from_std(&err, "-12a4", false), Some(ParseIntError::InvalidDigit { position: 0 }));
+        assert_eq!(ParseIntError::from_std(&err, "-12a4", true), Some(ParseIntError::InvalidDigit { position: 3 }));
+        // 数字でないバイトより前で桁あふれしたら桁あふれになる
+        check_all("4294967296x");
+        check_all("-2147483649x");
+        assert_eq!(u8::parse_fast("256x"), Err(ParseIntError::PosOverflow));
+        assert_eq!(i8::parse_fast("-129x"), Err(ParseIntError::NegOverflow));
+        assert_eq!(ParseIntError::PosOverflow.to_string(), "number too large to fit in target type");
+        check_all("--1");
+        check_all("+-1");
+        for len in 1..=40 {
+            check_all(&"9".repeat(len));
+            check_all(&format!("-1{}", "0".repeat(len)));
         }
     }
+
+    /// 8桁以下、16桁以下、それより長い数字列で読み方が分かれる
+    #[test]
+    fn digit_lengths() {
+        let digits = "9876543210".repeat(4);
+        for len in 1..=39 {
+            let s = &digits[..len];
+            check_all(s);
+            check_all(&format!("-{s}"));
+            check_all(&format!("+{s}"));
+            for i in 0..len {
+                let mut invalid = s.as_bytes().to_vec();
+                invalid[i] = b'x';
+                check_all(std::str::from_utf8(&invalid).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn prefix() {
+        assert_eq!(u32::parse_prefix(b"123abc"), Ok((123, 3)));
+        assert_eq!(i64::parse_prefix(b"-0042,1"), Ok((-42, 5)));
+        assert_eq!(u8::parse_prefix(b"+255"), Ok((255, 4)));
+        assert_eq!(u8::parse_prefix(b"256 "), Err(ParseIntError::PosOverflow));
+        assert_eq!(u32::parse_prefix(b"-5"), Err(ParseIntError::InvalidDigit { position: 0 }));
+        assert_eq!(i32::parse_prefix(b"+x"), Err(ParseIntError::InvalidDigit { position: 1 }));
+        assert_eq!(i32::parse_prefix(b"-"), Err(ParseIntError::InvalidDigit { position: 0 }));
+        assert_eq!(i32::parse_prefix(b" 1"), Err(ParseIntError::InvalidDigit { position: 0 }));
+        assert_eq!(i32::parse_prefix(b""), Err(ParseIntError::Empty));
+        assert_eq!(u64::parse_fast_bytes(b"12\xff"), Err(ParseIntError::InvalidDigit { position: 2 }));
+        assert!(PaddedBytes::new(b"1234567", 0).is_none());
+        assert!(PaddedBytes::new(b"12345678", 0).is_some());
+        assert!(PaddedBytes::new(b"12345678", usize::MAX).is_none());
+        assert_eq!(String::parse_fast_bytes(b"\xe3\x81\x82"), Ok("\u{3042}".to_owned()));
+        assert!(String::parse_fast_bytes(b"\xe3\x81").is_err());
+    }
 }
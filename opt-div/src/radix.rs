@@ -0,0 +1,372 @@
+//! 基数と桁区切りを指定した整数の変換
+//!
+//! 区切りがないとき、基数10は[`FromStrFast`](crate::FromStrFast)と同じ経路で、基数16と2はSWARで
+//! 8桁ずつ読む。それ以外は1桁ずつ読む。エラーはどの基数でも同じ規則で決める。
+
+use crate::{parse_u128, parse_u64, slice_as_sized, ParseIntError};
+
+/// 基数を指定して整数に変換する
+///
+/// ```
+/// use opt_div::FromStrRadixFast;
+///
+/// assert_eq!(u32::parse_fast_radix("ff", 16), Ok(255));
+/// assert_eq!(i64::parse_fast_radix_with_separator("-1,234,567", 10, b','), Ok(-1234567));
+/// ```
+pub trait FromStrRadixFast: Sized {
+    /// [`from_str_radix`](u32::from_str_radix)と同じ文字列を受け付ける
+    ///
+    /// # Panics
+    /// `radix`が`2..=36`の範囲にないとき
+    fn parse_fast_radix(s: &str, radix: u32) -> Result<Self, ParseIntError>;
+
+    /// 符号の後ろにある`separator`を読み飛ばす
+    ///
+    /// 区切りしかないときは符号の後ろの位置で[`ParseIntError::InvalidDigit`]になる。
+    ///
+    /// # Panics
+    /// `radix`が`2..=36`の範囲にないときや、`separator`がその基数の数字のとき
+    fn parse_fast_radix_with_separator(s: &str, radix: u32, separator: u8) -> Result<Self, ParseIntError>;
+}
+
+/// 8バイトが全て16進数字か
+#[inline]
+fn is_hex_digits8(n: u64) -> bool {
+    const ONES: u64 = 0x0101_0101_0101_0101;
+    const HIGH: u64 = 0x8080_8080_8080_8080;
+    // 全バイトが0x80未満なら、各バイトに足しても隣のバイトに繰り上がらない
+    let in_range = |x: u64, lo: u64, hi: u64| x.wrapping_add((0x80 - lo) * ONES) & !x.wrapping_add((0x7f - hi) * ONES);
+    let lower = n | (0x20 * ONES);
+    n & HIGH == 0 && (in_range(n, b'0' as u64, b'9' as u64) | in_range(lower, b'a' as u64, b'f' as u64)) & HIGH == HIGH
+}
+
+/// 8桁以下の16進数字列を読む
+#[inline]
+fn parse_hex8(s: &[u8]) -> Option<u64> {
+    const ONES: u64 = 0x0101_0101_0101_0101;
+    let n = u64::from_le_bytes(slice_as_sized::<_, 8>(s, b'0'));
+    if !is_hex_digits8(n) {
+        return None;
+    }
+    // 英字だけ0x40のbitが立っている
+    let mut n = (n & (0x0f * ONES)) + 9 * ((n >> 6) & ONES);
+    n = (n.wrapping_mul((16 << 8) + 1) >> 8) & 0x00ff_00ff_00ff_00ff;
+    n = (n.wrapping_mul((256 << 16) + 1) >> 16) & 0x0000_ffff_0000_ffff;
+    n = (n.wrapping_mul((65536 << 32) + 1) >> 32) & 0x0000_0000_ffff_ffff;
+    Some(n)
+}
+
+/// 8桁以下の2進数字列を読む
+#[inline]
+fn parse_bin8(s: &[u8]) -> Option<u64> {
+    const ONES: u64 = 0x0101_0101_0101_0101;
+    let n = u64::from_le_bytes(slice_as_sized::<_, 8>(s, b'0'));
+    if n & !ONES != 0x30 * ONES {
+        return None;
+    }
+    // 先頭の桁 (最下位バイト) が最上位bitに来る
+    Some((n & ONES).wrapping_mul(0x8040_2010_0804_0201) >> 56)
+}
+
+macro_rules! parse_pow2_digits {
+    ($($name:ident => $t:ty, $parse8:ident, $bits:expr),*) => {
+        $(
+            /// 符号を除いた数字列を8桁ずつ読む。桁あふれしたら`None`
+            fn $name(s: &[u8]) -> Option<$t> {
+                let max_len = (<$t>::BITS / $bits) as usize;
+                let s = if s.len() > max_len {
+                    &s[s.iter().position(|&c| c != b'0').unwrap_or(s.len())..]
+                } else {
+                    s
+                };
+                // 1桁が整数bitに対応するので、桁数が収まれば桁あふれしない
+                if s.len() > max_len {
+                    return None;
+                }
+                let head = match s.len() % 8 {
+                    0 => s.len().min(8),
+                    r => r,
+                };
+                let (head, rest) = s.split_at(head);
+                let mut n = $parse8(head)? as $t;
+                for chunk in rest.chunks_exact(8) {
+                    n = n << (8 * $bits) | $parse8(chunk)? as $t;
+                }
+                Some(n)
+            }
+        )*
+    };
+}
+
+parse_pow2_digits!(
+    parse_hex_u64 => u64, parse_hex8, 4,
+    parse_hex_u128 => u128, parse_hex8, 4,
+    parse_bin_u64 => u64, parse_bin8, 1,
+    parse_bin_u128 => u128, parse_bin8, 1
+);
+
+fn parse_fast_u64(s: &[u8], radix: u32) -> Option<u128> {
+    match radix {
+        10 => parse_u64(s, s.len()),
+        16 => parse_hex_u64(s),
+        2 => parse_bin_u64(s),
+        _ => None,
+    }
+    .map(u128::from)
+}
+
+fn parse_fast_u128(s: &[u8], radix: u32) -> Option<u128> {
+    match radix {
+        10 => parse_u128(s, s.len()),
+        16 => parse_hex_u128(s),
+        2 => parse_bin_u128(s),
+        _ => None,
+    }
+}
+
+/// 符号を除いた数字列を先頭から1桁ずつ読む
+///
+/// `offset`は元の文字列での`s`の開始位置、`limit`は絶対値の上限。
+pub(crate) fn parse_scalar(
+    s: &[u8],
+    offset: usize,
+    radix: u32,
+    separator: Option<u8>,
+    limit: u128,
+    negative: bool,
+) -> Result<u128, ParseIntError> {
+    let mut n = 0u128;
+    let mut empty = true;
+    for (i, &c) in s.iter().enumerate() {
+        if Some(c) == separator {
+            continue;
+        }
+        let Some(digit) = (c as char).to_digit(radix) else {
+            return Err(ParseIntError::InvalidDigit { position: offset + i });
+        };
+        empty = false;
+        match n.checked_mul(radix as u128).and_then(|n| n.checked_add(digit as u128)).filter(|&n| n <= limit) {
+            Some(next) => n = next,
+            None if negative => return Err(ParseIntError::NegOverflow),
+            None => return Err(ParseIntError::PosOverflow),
+        }
+    }
+    if empty {
+        return Err(ParseIntError::InvalidDigit { position: offset });
+    }
+    Ok(n)
+}
+
+/// 符号と絶対値を読む。絶対値は`max`か、負のときは`min_abs`以下
+fn parse_radix(
+    s: &[u8],
+    radix: u32,
+    separator: Option<u8>,
+    (max, min_abs): (u128, u128),
+    fast: fn(&[u8], u32) -> Option<u128>,
+) -> Result<(bool, u128), ParseIntError> {
+    assert!((2..=36).contains(&radix), "radix must lie in the range [2, 36], got {radix}");
+    if let Some(separator) = separator {
+        assert!((separator as char).to_digit(radix).is_none(), "separator {separator:?} is a digit in radix {radix}");
+    }
+    let (negative, offset, s) = match s {
+        [] => return Err(ParseIntError::Empty),
+        [b'+' | b'-'] => return Err(ParseIntError::InvalidDigit { position: 0 }),
+        // 符号なし整数では`-`自体が不正な文字
+        [b'-', ..] if min_abs == 0 => return Err(ParseIntError::InvalidDigit { position: 0 }),
+        [b'-', s @ ..] => (true, 1, s),
+        [b'+', s @ ..] => (false, 1, s),
+        s => (false, 0, s),
+    };
+    let limit = if negative { min_abs } else { max };
+    let fast = if separator.is_none() { fast(s, radix) } else { None };
+    match fast.filter(|&n| n <= limit) {
+        Some(n) => Ok((negative, n)),
+        None => parse_scalar(s, offset, radix, separator, limit, negative).map(|n| (negative, n)),
+    }
+}
+
+macro_rules! impl_radix {
+    ($($t:ty => $fast:ident),*) => {
+        $(
+            impl FromStrRadixFast for $t {
+                fn parse_fast_radix(s: &str, radix: u32) -> Result<Self, ParseIntError> {
+                    let limits = (<$t>::MAX as u128, <$t>::MIN.abs_diff(0) as u128);
+                    let (negative, n) = parse_radix(s.as_bytes(), radix, None, limits, $fast)?;
+                    Ok(if negative { (n as $t).wrapping_neg() } else { n as $t })
+                }
+
+                fn parse_fast_radix_with_separator(s: &str, radix: u32, separator: u8) -> Result<Self, ParseIntError> {
+                    let limits = (<$t>::MAX as u128, <$t>::MIN.abs_diff(0) as u128);
+                    let (negative, n) = parse_radix(s.as_bytes(), radix, Some(separator), limits, $fast)?;
+                    Ok(if negative { (n as $t).wrapping_neg() } else { n as $t })
+                }
+            }
+        )*
+    };
+}
+
+impl_radix!(
+    u8 => parse_fast_u64,
+    u16 => parse_fast_u64,
+    u32 => parse_fast_u64,
+    u64 => parse_fast_u64,
+    usize => parse_fast_u64,
+    u128 => parse_fast_u128,
+    i8 => parse_fast_u64,
+    i16 => parse_fast_u64,
+    i32 => parse_fast_u64,
+    i64 => parse_fast_u64,
+    isize => parse_fast_u64,
+    i128 => parse_fast_u128
+);
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Debug;
+
+    use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+    use super::*;
+
+    trait FromStrRadix: Sized {
+        fn from_str_radix(s: &str, radix: u32) -> Result<Self, std::num::ParseIntError>;
+    }
+
+    macro_rules! impl_std {
+        ($($t:ty),*) => {
+            $(
+                impl FromStrRadix for $t {
+                    fn from_str_radix(s: &str, radix: u32) -> Result<Self, std::num::ParseIntError> {
+                        <$t>::from_str_radix(s, radix)
+                    }
+                }
+            )*
+        };
+    }
+
+    impl_std!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+    /// 種類は標準ライブラリと同じで、数字でない位置は先頭の数字でないバイトを指す
+    fn check<T: FromStrRadixFast + FromStrRadix + PartialEq + Debug>(s: &str, radix: u32) {
+        let actual = T::parse_fast_radix(s, radix);
+        match T::from_str_radix(s, radix) {
+            Ok(expected) => assert_eq!(actual, Ok(expected), "{s:?} in radix {radix}"),
+            Err(e) => {
+                let actual = actual.expect_err(s);
+                assert_eq!(actual.kind(), *e.kind(), "{s:?} in radix {radix}");
+                if let ParseIntError::InvalidDigit { position } = actual {
+                    if s.starts_with('-') && T::from_str_radix("-1", radix).is_err() {
+                        assert_eq!(position, 0, "{s:?} in radix {radix}");
+                        return;
+                    }
+                    let digits = s.strip_prefix(['+', '-']).filter(|d| !d.is_empty()).unwrap_or(s);
+                    let offset = s.len() - digits.len();
+                    let expected = digits.bytes().position(|c| !(c as char).is_digit(radix)).map_or(0, |i| offset + i);
+                    assert_eq!(position, expected, "{s:?} in radix {radix}");
+                }
+            }
+        }
+    }
+
+    fn check_all(s: &str, radix: u32) {
+        check::<u8>(s, radix);
+        check::<u16>(s, radix);
+        check::<u32>(s, radix);
+        check::<u64>(s, radix);
+        check::<u128>(s, radix);
+        check::<i8>(s, radix);
+        check::<i16>(s, radix);
+        check::<i32>(s, radix);
+        check::<i64>(s, radix);
+        check::<i128>(s, radix);
+    }
+
+    #[test]
+    fn swar_kernels() {
+        for n in [0u64, 1, 0xdead_beef, 0x0123_4567, 0xffff_ffff, 0xa0b0_c0d0] {
+            for s in [format!("{n:08x}"), format!("{n:08X}"), format!("{n:x}")] {
+                assert_eq!(parse_hex8(s.as_bytes()), Some(n), "{s}");
+            }
+        }
+        for n in 0..=255u64 {
+            assert_eq!(parse_bin8(format!("{n:08b}").as_bytes()), Some(n));
+            assert_eq!(parse_bin8(format!("{n:b}").as_bytes()), Some(n));
+        }
+        // 範囲の前後のバイト
+        for c in [b'/', b':', b'@', b'G', b'`', b'g', b' ', 0x80, 0xc6, 0xff] {
+            let mut s = *b"01234567";
+            for i in 0..8 {
+                let saved = s[i];
+                s[i] = c;
+                assert_eq!(parse_hex8(&s), None, "{s:?}");
+                s[i] = saved;
+            }
+            assert_eq!(parse_bin8(&[b'0', b'1', c]), None);
+        }
+        assert_eq!(parse_bin8(b"2"), None);
+    }
+
+    #[test]
+    fn against_std() {
+        let mut rng = StdRng::seed_from_u64(47);
+        for radix in [2, 8, 10, 16, 36] {
+            for s in ["", "+", "-", "0", "-0", "+1", "z", "Z", "-Z", "_1", "1_", "ff", "FF", "-80", "-81", "g"] {
+                check_all(s, radix);
+            }
+            for _ in 0..20_000 {
+                let n = rng.gen::<u128>() >> rng.gen_range(0..128);
+                let mut s = match radix {
+                    2 => format!("{n:b}"),
+                    8 => format!("{n:o}"),
+                    16 if rng.gen() => format!("{n:X}"),
+                    16 => format!("{n:x}"),
+                    _ => n.to_string(),
+                };
+                if rng.gen_ratio(1, 4) {
+                    s.insert_str(0, &"0".repeat(rng.gen_range(1..40)));
+                }
+                if rng.gen_ratio(1, 4) {
+                    let i = rng.gen_range(0..=s.len());
+                    s.insert(i, *b"/:@G`g_ \xc3".choose(&mut rng).unwrap() as char);
+                }
+                check_all(&s, radix);
+                check_all(&format!("-{s}"), radix);
+                check_all(&format!("+{s}"), radix);
+            }
+        }
+    }
+
+    #[test]
+    fn separators() {
+        assert_eq!(u32::parse_fast_radix_with_separator("1,234,567", 10, b','), Ok(1234567));
+        assert_eq!(u64::parse_fast_radix_with_separator("dead_beef_cafe", 16, b'_'), Ok(0xdead_beef_cafe));
+        assert_eq!(u8::parse_fast_radix_with_separator("+1010_0101", 2, b'_'), Ok(0b1010_0101));
+        assert_eq!(i8::parse_fast_radix_with_separator("-1000_0000", 2, b'_'), Ok(i8::MIN));
+        assert_eq!(i8::parse_fast_radix_with_separator("-1000_0001", 2, b'_'), Err(ParseIntError::NegOverflow));
+        assert_eq!(i32::parse_fast_radix_with_separator("__1__", 10, b'_'), Ok(1));
+        assert_eq!(i32::parse_fast_radix_with_separator("-_", 10, b'_'), Err(ParseIntError::InvalidDigit { position: 1 }));
+        assert_eq!(u32::parse_fast_radix_with_separator("1,2x", 10, b','), Err(ParseIntError::InvalidDigit { position: 3 }));
+        assert_eq!(u32::parse_fast_radix_with_separator("-1,2", 10, b','), Err(ParseIntError::InvalidDigit { position: 0 }));
+        assert_eq!(u32::parse_fast_radix_with_separator("-1,x", 10, b','), Err(ParseIntError::InvalidDigit { position: 0 }));
+        assert_eq!(u64::parse_fast_radix("-ffz", 16), Err(ParseIntError::InvalidDigit { position: 0 }));
+        assert_eq!(u32::parse_fast_radix("1_0", 10), Err(ParseIntError::InvalidDigit { position: 1 }));
+        for n in [0u128, 1, 999, 1000, u64::MAX as u128, u128::MAX] {
+            let digits = n.to_string().into_bytes();
+            let grouped = digits.rchunks(3).rev().map(|c| std::str::from_utf8(c).unwrap()).collect::<Vec<_>>().join(",");
+            assert_eq!(u128::parse_fast_radix_with_separator(&grouped, 10, b','), Ok(n), "{grouped}");
+        }
+    }
+
+    #[test]
+    #[should_panic = "radix must lie in the range"]
+    fn invalid_radix() {
+        let _ = u32::parse_fast_radix("1", 37);
+    }
+
+    #[test]
+    #[should_panic = "is a digit in radix"]
+    fn invalid_separator() {
+        let _ = u32::parse_fast_radix_with_separator("1", 16, b'a');
+    }
+}
@@ -0,0 +1,256 @@
+//! 空白区切りのトークンを読むスキャナ
+
+use std::io::{self, Read};
+
+use crate::{error::ScanError, FromStrFast, PaddedBytes};
+
+/// 入力を大きな塊で読み、ASCIIの空白で区切ったトークンを[`FromStrFast`]で変換する
+///
+/// 区切りはバイト単位で探し、UTF-8の検証はしない。バッファの後ろに余白を確保しておき、
+/// トークンは[`FromStrFast::parse_fast_padded`]でバッファの中のまま変換する。
+///
+/// ```
+/// use opt_div::FastScanner;
+///
+/// let mut sc = FastScanner::new("3\n1 -2 3\n".as_bytes());
+/// let n = sc.scan::<usize>().unwrap();
+/// let a = (0..n).map(|_| sc.scan::<i32>().unwrap()).collect::<Vec<_>>();
+/// assert_eq!(a, [1, -2, 3]);
+/// ```
+#[derive(Debug)]
+pub struct FastScanner<R> {
+    reader: R,
+    /// 末尾の[`PaddedBytes::PADDING`]バイトには読み込まない
+    buf: Vec<u8>,
+    /// `buf[pos..end]`が未読
+    pos: usize,
+    end: usize,
+}
+
+impl<R: Read> FastScanner<R> {
+    pub fn new(reader: R) -> Self {
+        Self::with_capacity(1 << 16, reader)
+    }
+
+    /// 1回に読むバイト数を指定する。これより長いトークンがあればバッファを伸ばす
+    pub fn with_capacity(capacity: usize, reader: R) -> Self {
+        Self {
+            reader,
+            buf: vec![0; capacity.max(1) + PaddedBytes::PADDING],
+            pos: 0,
+            end: 0,
+        }
+    }
+
+    /// 未読の部分をバッファの先頭に寄せてから続きを読む。入力の終わりに達していたら`false`
+    fn fill(&mut self) -> io::Result<bool> {
+        if self.pos > 0 {
+            self.buf.copy_within(self.pos..self.end, 0);
+            self.end -= self.pos;
+            self.pos = 0;
+        }
+        let capacity = self.buf.len() - PaddedBytes::PADDING;
+        if self.end == capacity {
+            self.buf.resize(2 * capacity + PaddedBytes::PADDING, 0);
+        }
+        let capacity = self.buf.len() - PaddedBytes::PADDING;
+        loop {
+            match self.reader.read(&mut self.buf[self.end..capacity]) {
+                Ok(0) => return Ok(false),
+                Ok(n) => {
+                    self.end += n;
+                    return Ok(true);
+                }
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// 次のトークン。入力の終わりに達していたら`None`
+    ///
+    /// バッファの境界をまたぐトークンもつなげて返す。
+    pub fn next_token(&mut self) -> io::Result<Option<&[u8]>> {
+        Ok(self.next_range()?.map(|(start, len)| &self.buf[start..start + len]))
+    }
+
+    /// 次のトークンの`buf`での位置と長さ
+    #[inline]
+    fn next_range(&mut self) -> io::Result<Option<(usize, usize)>> {
+        // 区切りはたいてい1バイトで、トークンの後ろの区切りも読み込まれている
+        let mut start = self.pos;
+        while start < self.end && self.buf[start].is_ascii_whitespace() {
+            start += 1;
+        }
+        let mut len = 0;
+        while start + len < self.end {
+            // `buf`の末尾には余白があるので8バイト読める
+            let chunk = self.buf[start + len..][..8].try_into().unwrap();
+            let i = first_control(u64::from_le_bytes(chunk));
+            len += i;
+            if i < 8 {
+                break;
+            }
+        }
+        if start + len < self.end && len > 0 && self.buf[start + len].is_ascii_whitespace() {
+            self.pos = start + len;
+            return Ok(Some((start, len)));
+        }
+        self.pos = start;
+        self.next_range_slow()
+    }
+
+    /// バッファを読み足しながら次のトークンを探す
+    #[inline(never)]
+    fn next_range_slow(&mut self) -> io::Result<Option<(usize, usize)>> {
+        loop {
+            match self.buf[self.pos..self.end].iter().position(|c| !c.is_ascii_whitespace()) {
+                Some(i) => {
+                    self.pos += i;
+                    break;
+                }
+                None => {
+                    self.pos = self.end;
+                    if !self.fill()? {
+                        return Ok(None);
+                    }
+                }
+            }
+        }
+        // トークンの先頭から読み終えたバイト数
+        let mut len = 0;
+        loop {
+            let rest = &self.buf[self.pos + len..self.end];
+            if let Some(i) = rest.iter().position(u8::is_ascii_whitespace) {
+                len += i;
+                break;
+            }
+            len = self.end - self.pos;
+            if !self.fill()? {
+                break;
+            }
+        }
+        let start = self.pos;
+        self.pos += len;
+        Ok(Some((start, len)))
+    }
+
+    /// 次のトークンを`T`に変換する
+    #[inline]
+    pub fn scan<T: FromStrFast>(&mut self) -> Result<T, ScanError<T::Err>> {
+        let (start, len) = self.next_range().map_err(ScanError::Io)?.ok_or(ScanError::Eof)?;
+        let token = PaddedBytes::new(&self.buf[start..], len).expect("buffer keeps padding after the data");
+        T::parse_fast_padded(token).map_err(ScanError::Parse)
+    }
+}
+
+/// 8バイトのうち最初の`0x20`以下のバイトの位置。なければ8
+///
+/// ASCIIの空白はどれも`0x20`以下なので、区切りの候補をまとめて探せる。最下位の候補より
+/// 上のバイトは借りの伝播で誤って立つことがあるが、最下位の候補は正しい。
+#[inline]
+fn first_control(n: u64) -> usize {
+    const ONES: u64 = 0x0101_0101_0101_0101;
+    let found = n.wrapping_sub(0x21 * ONES) & !n & (0x80 * ONES);
+    found.trailing_zeros() as usize / 8
+}
+
+/// [`FastScanner`]から値を読む。失敗したらパニックする
+///
+/// 型は1トークンで書けるもの、`(型, ...)`、`[型; 個数]`を組み合わせられる。`$sc`は何度も
+/// 評価されるので変数を渡す。
+///
+/// ```
+/// use opt_div::{scan, FastScanner};
+///
+/// let mut sc = FastScanner::new("2 3\n1 2 3\n4 5 6\nabc 7 8".as_bytes());
+/// let (h, w) = scan!(sc => usize, usize);
+/// let grid = scan!(sc => [[u8; w]; h]);
+/// let (s, pair) = scan!(sc => String, (u8, u8));
+/// assert_eq!(grid, [[1, 2, 3], [4, 5, 6]]);
+/// assert_eq!((s.as_str(), pair), ("abc", (7, 8)));
+/// ```
+#[macro_export]
+macro_rules! scan {
+    ($sc:expr => [$t:tt; $n:expr]) => {
+        (0..$n).map(|_| $crate::scan!($sc => $t)).collect::<Vec<_>>()
+    };
+    ($sc:expr => ($($t:tt),* $(,)?)) => {
+        ($($crate::scan!($sc => $t)),*)
+    };
+    ($sc:expr => $t:ty) => {
+        $sc.scan::<$t>().unwrap()
+    };
+    ($sc:expr => $($t:tt),+ $(,)?) => {
+        ($($crate::scan!($sc => $t)),*)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ParseIntError;
+
+    /// 1回に`chunk`バイトずつしか返さないリーダ
+    struct Chunked<'a> {
+        data: &'a [u8],
+        chunk: usize,
+    }
+
+    impl Read for Chunked<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = self.chunk.min(buf.len()).min(self.data.len());
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn buffer_boundaries() {
+        let input = "  12345678901234567890 -7\n\t+42\r\n\n 0 18446744073709551615 abc  12345678 \
+            1234567812345678\x0ca\x01b\x00 \x7f\u{3042}\x1f";
+        let expected = input.split_ascii_whitespace().collect::<Vec<_>>();
+        for capacity in [1, 2, 3, 7, 64] {
+            for chunk in [1, 2, 5, 100] {
+                let mut sc = FastScanner::with_capacity(capacity, Chunked { data: input.as_bytes(), chunk });
+                let mut tokens = Vec::new();
+                while let Some(token) = sc.next_token().unwrap() {
+                    tokens.push(String::from_utf8(token.to_vec()).unwrap());
+                }
+                assert_eq!(tokens, expected, "capacity {capacity}, chunk {chunk}");
+                assert!(sc.next_token().unwrap().is_none());
+            }
+        }
+    }
+
+    #[test]
+    fn first_control_byte() {
+        for c in 0..=u8::MAX {
+            for i in 0..8 {
+                let mut bytes = *b"abcdefgh";
+                bytes[i] = c;
+                let expected = if c <= b' ' { i } else { 8 };
+                assert_eq!(first_control(u64::from_le_bytes(bytes)), expected, "{c:#x} at {i}");
+            }
+        }
+        // 最下位の候補より上のバイトは見ない
+        assert_eq!(first_control(u64::from_le_bytes(*b"1 \x21\x00\x80 \xff\x20")), 1);
+    }
+
+    #[test]
+    fn scan_values() {
+        let mut sc = FastScanner::with_capacity(4, "3\n1 -2 300\n(x) 256 \u{3042}".as_bytes());
+        let n = scan!(sc => usize);
+        assert_eq!(scan!(sc => [i64; n]), [1, -2, 300]);
+        assert_eq!(scan!(sc => String), "(x)");
+        assert!(matches!(sc.scan::<u8>(), Err(ScanError::Parse(ParseIntError::PosOverflow))));
+        assert_eq!(scan!(sc => String), "\u{3042}");
+        assert!(matches!(sc.scan::<u8>(), Err(ScanError::Eof)));
+
+        let mut sc = FastScanner::new(&b"\xff 1 2 3 4"[..]);
+        assert!(matches!(sc.scan::<String>(), Err(ScanError::Parse(_))));
+        let (a, (b, c), d) = scan!(sc => u8, (u16, i32), [u64; 1]);
+        assert_eq!((a, b, c, d), (1, 2, 3, vec![4]));
+    }
+}
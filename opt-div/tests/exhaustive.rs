@@ -0,0 +1,73 @@
+//! `u32`で`parse_fast`と`str::parse`を比べる
+//!
+//! 普段の`cargo test`で通るのは桁数の境目と最大値の近くを調べる[`boundaries`]だけ。全ての`u32`を
+//! 調べる[`all_u32`]は時間がかかるので無視してあり、解析の経路を変えたときに手で実行する。
+//!
+//! ```text
+//! cargo test -p opt-div --release --test exhaustive -- --ignored
+//! ```
+
+use std::{io::Write, thread};
+
+use opt_div::{FromStrFast, ParseIntError};
+
+/// 数字の前に付ける文字列。最後のものは`u32`の最大桁数を超えて先頭の0を読み飛ばす経路を通る
+const PREFIXES: [&[u8]; 5] = [b"", b"+", b"0", b"+000", b"000000000000"];
+
+/// 10進の数字列を1増やす
+fn increment(digits: &mut Vec<u8>) {
+    for c in digits.iter_mut().rev() {
+        if *c == b'9' {
+            *c = b'0';
+        } else {
+            *c += 1;
+            return;
+        }
+    }
+    digits.insert(0, b'1');
+}
+
+/// `start..=end`の値を全ての書式で比べる
+fn check_range(start: u32, end: u32) {
+    let mut digits = Vec::new();
+    write!(digits, "{start}").unwrap();
+    let mut buf = Vec::new();
+    for n in start..=end {
+        for prefix in PREFIXES {
+            buf.clear();
+            buf.extend_from_slice(prefix);
+            buf.extend_from_slice(&digits);
+            let s = std::str::from_utf8(&buf).unwrap();
+            let expected = s.parse::<u32>().map_err(|e| ParseIntError::from_std(&e, s, false).unwrap());
+            assert_eq!(u32::parse_fast(s), expected, "{s:?}");
+        }
+        debug_assert_eq!(digits, n.to_string().as_bytes());
+        increment(&mut digits);
+    }
+}
+
+/// 桁数が変わる前後と最大値の近くだけ
+#[test]
+fn boundaries() {
+    check_range(0, 100_000);
+    for digits in 6..10 {
+        let n = 10u32.pow(digits);
+        check_range(n - 1000, n + 1000);
+    }
+    check_range(u32::MAX - 100_000, u32::MAX);
+}
+
+/// 全ての`u32`。1コアでは10分以上かかる
+#[test]
+#[ignore = "takes minutes even in release mode; run with --release -- --ignored"]
+fn all_u32() {
+    let threads = thread::available_parallelism().map_or(1, |n| n.get()) as u64;
+    let chunk = (u32::MAX as u64 + 1).div_ceil(threads);
+    thread::scope(|scope| {
+        for i in 0..threads {
+            let start = i * chunk;
+            let end = ((i + 1) * chunk - 1).min(u32::MAX as u64);
+            scope.spawn(move || check_range(start as u32, end as u32));
+        }
+    });
+}
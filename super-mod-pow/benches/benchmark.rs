@@ -0,0 +1,124 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use super_mod_pow::{
+    mpow, mpow_batch, mpow_fast,
+    reduction::{Barrett, Montgomery},
+    solve, solve_batch,
+};
+
+fn random_cases<R: Rng>(n: usize, rng: &mut R, odd: bool) -> Vec<(u64, u64, u64)> {
+    (0..n)
+        .map(|_| {
+            let modulo = rng.gen_range(2..=u64::MAX);
+            let modulo = if odd { modulo | 1 } else { modulo & !1 };
+            (rng.gen_range(0..modulo), rng.gen(), modulo)
+        })
+        .collect()
+}
+
+fn bench_mod_pow(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(100);
+    for (name, odd) in [("odd modulus", true), ("even modulus", false)] {
+        let data = random_cases(1000, &mut rng, odd);
+        let mut group = c.benchmark_group(format!("modular exponentiation ({name})"));
+        group.bench_function("mpow", |b| {
+            b.iter(|| {
+                for &(base, exp, modulo) in &data {
+                    black_box(mpow(black_box(base), black_box(exp), black_box(modulo)));
+                }
+            })
+        });
+        group.bench_function("mpow_fast", |b| {
+            b.iter(|| {
+                for &(base, exp, modulo) in &data {
+                    black_box(mpow_fast(black_box(base), black_box(exp), black_box(modulo)));
+                }
+            })
+        });
+        // 法ごとの前計算は測らない
+        let barrett = data.iter().map(|&(_, _, modulo)| Barrett::new(modulo)).collect::<Vec<_>>();
+        group.bench_function("Barrett::pow", |b| {
+            b.iter(|| {
+                for (&(base, exp, _), barrett) in data.iter().zip(&barrett) {
+                    black_box(barrett.pow(black_box(base), black_box(exp)));
+                }
+            })
+        });
+        if odd {
+            let montgomery = data.iter().map(|&(_, _, modulo)| Montgomery::new(modulo)).collect::<Vec<_>>();
+            group.bench_function("Montgomery::pow", |b| {
+                b.iter(|| {
+                    for (&(base, exp, _), montgomery) in data.iter().zip(&montgomery) {
+                        black_box(montgomery.pow(black_box(base), black_box(exp)));
+                    }
+                })
+            });
+        }
+        group.finish();
+    }
+}
+
+/// 法を固定して積を繰り返す
+fn bench_mul(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(27);
+    let modulo = rng.gen::<u64>() | 1;
+    let xs = (0..1000).map(|_| rng.gen_range(0..modulo)).collect::<Vec<_>>();
+    let barrett = Barrett::new(modulo);
+    let montgomery = Montgomery::new(modulo);
+    let mut group = c.benchmark_group("modular multiplication");
+    group.bench_function("u128 %", |b| {
+        b.iter(|| xs.iter().fold(1, |acc, &x| (acc as u128 * x as u128 % black_box(modulo) as u128) as u64))
+    });
+    group.bench_function("Barrett::mul_mod", |b| {
+        b.iter(|| xs.iter().fold(1, |acc, &x| black_box(&barrett).mul_mod(acc, x)))
+    });
+    group.bench_function("Montgomery::mul_mod", |b| {
+        b.iter(|| xs.iter().fold(1, |acc, &x| black_box(&montgomery).mul_mod(acc, x)))
+    });
+    // 変換を最初と最後の1回ずつにして、途中はモンゴメリ表現のまま掛ける
+    let xs_mont = xs.iter().map(|&x| montgomery.to_mont(x)).collect::<Vec<_>>();
+    group.bench_function("Montgomery::mul_mont", |b| {
+        b.iter(|| {
+            let montgomery = black_box(&montgomery);
+            montgomery.from_mont(xs_mont.iter().fold(montgomery.to_mont(1), |acc, &x| montgomery.mul_mont(acc, x)))
+        })
+    });
+    group.finish();
+}
+
+/// 底と法を固定して多数の指数で冪乗する
+fn bench_batch(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(38);
+    let exps = (0..1000).map(|_| rng.gen()).collect::<Vec<u64>>();
+    let queries = (0..1000).map(|_| (rng.gen(), rng.gen())).collect::<Vec<(u64, u64)>>();
+    for (name, modulo) in [("odd modulus", u64::MAX - 58), ("even modulus", 1_234_567_890_123_456_780)] {
+        let base = rng.gen_range(0..modulo);
+        let mut group = c.benchmark_group(format!("batch exponentiation ({name})"));
+        group.bench_function("mpow", |b| {
+            b.iter(|| {
+                for &exp in &exps {
+                    black_box(mpow(black_box(base), black_box(exp), black_box(modulo)));
+                }
+            })
+        });
+        group.bench_function("mpow_batch", |b| {
+            b.iter(|| black_box(mpow_batch(black_box(base), black_box(&exps), black_box(modulo))))
+        });
+        group.bench_function("solve", |b| {
+            b.iter(|| {
+                for &(e, f) in &queries {
+                    black_box(solve(black_box(base), black_box(e), black_box(f), black_box(modulo)));
+                }
+            })
+        });
+        group.bench_function("solve_batch", |b| {
+            b.iter(|| black_box(solve_batch(black_box(base), black_box(&queries), black_box(modulo))))
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(bench, bench_mod_pow, bench_mul, bench_batch);
+criterion_main!(bench);
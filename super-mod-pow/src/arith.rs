@@ -0,0 +1,193 @@
+//! 素因数分解から求まる数論的関数
+//!
+//! 各関数は[`Factorization`]のメソッドとしても使えるので、同じ`n`について複数の値が必要なときは
+//! 素因数分解を1回で済ませられる。
+
+use crate::{
+    factor::{factorize, Factorization},
+    mpow,
+    uint::gcd,
+    UInt,
+};
+
+fn lcm(a: u64, b: u64) -> u64 {
+    (a / gcd(a, b)).saturating_mul(b)
+}
+
+/// 素数冪`p^k` (`k >= 1`)のカーマイケル関数。`T`に収まらなければ`None`
+pub(crate) fn carmichael_lambda_prime_power<T: UInt>(p: &T, k: u32) -> Option<T> {
+    let two = T::from(2u32);
+    if *p == two {
+        // λ(2) = 1, λ(4) = 2, λ(2^k) = 2^(k-2)
+        num_traits::checked_pow(two, k.saturating_sub(1 + u32::from(k >= 3)) as usize)
+    } else {
+        num_traits::checked_pow(p.clone(), (k - 1) as usize)?.checked_mul(&(p.clone() - T::one()))
+    }
+}
+
+impl Factorization {
+    /// オイラーのφ関数
+    pub fn euler_phi(&self) -> u64 {
+        self.iter().fold(1, |phi, &(p, k)| phi.saturating_mul(p.saturating_pow(k - 1).saturating_mul(p - 1)))
+    }
+
+    /// カーマイケルのλ関数。`(Z/nZ)^*`の全ての元の位数の最小公倍数
+    pub fn carmichael_lambda(&self) -> u64 {
+        self.iter()
+            .map(|&(p, k)| carmichael_lambda_prime_power(&p, k).unwrap_or(u64::MAX))
+            .fold(1, lcm)
+    }
+
+    /// 昇順に並んだ約数
+    pub fn divisors(&self) -> Vec<u64> {
+        let mut divisors = vec![1u64];
+        for &(p, k) in self {
+            let len = divisors.len();
+            let mut pk = 1u64;
+            for _ in 0..k {
+                let Some(next) = pk.checked_mul(p) else {
+                    break;
+                };
+                pk = next;
+                for i in 0..len {
+                    if let Some(d) = divisors[i].checked_mul(pk) {
+                        divisors.push(d);
+                    }
+                }
+            }
+        }
+        divisors.sort_unstable();
+        divisors
+    }
+
+    /// `a^t ≡ 1 (mod n)`となる最小の正の整数`t`
+    ///
+    /// `a`と`n`が互いに素でないときは`None`
+    pub fn multiplicative_order(&self, a: u64) -> Option<u64> {
+        let n = self.value();
+        if n == 0 || gcd(a, n) != 1 {
+            return None;
+        }
+        let lambda = self.carmichael_lambda();
+        let mut order = lambda;
+        for &(q, _) in &factorize(lambda) {
+            while order.is_multiple_of(q) && mpow(a, order / q, n) == 1 % n {
+                order /= q;
+            }
+        }
+        Some(order)
+    }
+
+    /// 最小の原始根。原始根が存在しないときは`None`
+    ///
+    /// `n = 1`のときは`Some(0)`を返す。
+    pub fn primitive_root(&self) -> Option<u64> {
+        let n = self.value();
+        let has_root = match self.iter().as_slice() {
+            [] | [(2, 1..=2)] => true,
+            [(2, _)] => false,
+            [(_, _)] | [(2, 1), (_, _)] => true,
+            _ => false,
+        };
+        if !has_root {
+            return None;
+        }
+        if n <= 2 {
+            return Some(n - 1);
+        }
+        let phi = self.euler_phi();
+        let phi_primes = factorize(phi);
+        (2..n).find(|&g| gcd(g, n) == 1 && phi_primes.primes().all(|q| mpow(g, phi / q, n) != 1))
+    }
+}
+
+/// オイラーのφ関数。`n = 0`のときは0
+pub fn euler_phi(n: u64) -> u64 {
+    if n == 0 {
+        return 0;
+    }
+    factorize(n).euler_phi()
+}
+
+/// カーマイケルのλ関数。`n = 0`のときは0
+pub fn carmichael_lambda(n: u64) -> u64 {
+    if n == 0 {
+        return 0;
+    }
+    factorize(n).carmichael_lambda()
+}
+
+/// `a`の`n`を法とする位数
+pub fn multiplicative_order(a: u64, n: u64) -> Option<u64> {
+    if n == 0 {
+        return None;
+    }
+    factorize(n).multiplicative_order(a)
+}
+
+/// `n`を法とする最小の原始根
+pub fn primitive_root(n: u64) -> Option<u64> {
+    if n == 0 {
+        return None;
+    }
+    factorize(n).primitive_root()
+}
+
+/// `n`の約数を昇順に返す。`n = 0`のときは空
+pub fn divisors(n: u64) -> Vec<u64> {
+    if n == 0 {
+        return Vec::new();
+    }
+    factorize(n).divisors()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order_naive(a: u64, n: u64) -> Option<u64> {
+        (gcd(a, n) == 1).then(|| (1..=n).find(|&t| mpow(a, t, n) == 1 % n).unwrap())
+    }
+
+    #[test]
+    fn against_naive() {
+        for n in 1..=300u64 {
+            let units = (0..n).filter(|&a| gcd(a, n) == 1).collect::<Vec<_>>();
+            assert_eq!(euler_phi(n), units.len() as u64);
+            let orders = units.iter().map(|&a| order_naive(a, n).unwrap()).collect::<Vec<_>>();
+            assert_eq!(carmichael_lambda(n), orders.iter().copied().fold(1, lcm), "{n}");
+            for a in 0..n {
+                assert_eq!(multiplicative_order(a, n), order_naive(a, n), "{a} mod {n}");
+            }
+            let root = units.iter().zip(&orders).find(|&(_, &o)| o == units.len() as u64);
+            assert_eq!(primitive_root(n), root.map(|(&g, _)| g % n), "{n}");
+            assert_eq!(divisors(n), (1..=n).filter(|&d| n.is_multiple_of(d)).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn large() {
+        let p = u64::MAX - 58;
+        assert_eq!(euler_phi(p), p - 1);
+        assert_eq!(carmichael_lambda(1 << 63), 1 << 61);
+        assert_eq!(multiplicative_order(2, 1_000_000_007), Some(500_000_003));
+        assert_eq!(primitive_root(998_244_353), Some(3));
+        assert_eq!(divisors(u64::MAX).len(), 128);
+    }
+
+    /// `u64`に収まらない値の分解を与えてもパニックしない
+    #[test]
+    fn oversized_factorization() {
+        let f = Factorization::from_iter([(2, 0), (0, 3), (1, 2), (3, 200), (2, 100)]);
+        assert_eq!(f.iter().as_slice(), &[(2, 100), (3, 200)]);
+        assert_eq!(f.value(), u64::MAX);
+        assert_eq!(f.euler_phi(), u64::MAX);
+        assert_eq!(f.carmichael_lambda(), u64::MAX);
+        // u64に収まる 2^i * 3^j だけが残る
+        let expected = (0..64u32)
+            .map(|i| (0..=40u32).filter(|&j| (1u64 << i).checked_mul(3u64.pow(j)).is_some()).count())
+            .sum::<usize>();
+        assert_eq!(f.divisors().len(), expected);
+        let _ = f.multiplicative_order(5);
+    }
+}
@@ -0,0 +1,212 @@
+//! 同じ底や法で何度も冪乗するときの前計算
+//!
+//! [`PowTable`]は底と法を固定した窓表を作り、1回の冪乗を表引きと高々15回の乗算で済ませる。
+//! [`Solver`]は`a`と`n`を固定して`a^(b^c) mod n`を繰り返し求める。
+
+use crate::{crt, factorize, reduction::FastModulus, tower, ModPowError};
+
+/// 窓幅 (bit)
+const WINDOW: u32 = 4;
+const ROWS: usize = (u64::BITS / WINDOW) as usize;
+
+/// 底と法を固定した冪乗表
+///
+/// `table[i][d] = base^(d * 16^i)`を内部表現で持つ。
+#[derive(Clone, Debug)]
+pub struct PowTable {
+    base: u64,
+    modulus: u64,
+    fast: FastModulus,
+    table: Box<[[u64; 1 << WINDOW]; ROWS]>,
+}
+
+impl PowTable {
+    /// `modulus == 0`のときは、[`mpow`](crate::mpow)と同じく常に0を返す表になる
+    pub fn new(base: u64, modulus: u64) -> Self {
+        Self::try_new(base, modulus).unwrap_or_else(|_| {
+            // 法1の表は全て0
+            Self { modulus, ..Self::build(0, FastModulus::new(1)) }
+        })
+    }
+
+    pub fn try_new(base: u64, modulus: u64) -> Result<Self, ModPowError> {
+        Ok(Self::build(base, FastModulus::try_new(modulus)?))
+    }
+
+    fn build(base: u64, fast: FastModulus) -> Self {
+        let one = fast.encode(1);
+        let mut table = Box::new([[one; 1 << WINDOW]; ROWS]);
+        let mut power = fast.encode(base);
+        for row in table.iter_mut() {
+            for d in 1..row.len() {
+                row[d] = fast.mul_repr(row[d - 1], power);
+            }
+            // 次の行の底は base^(16^(i+1))
+            power = fast.mul_repr(row[row.len() - 1], power);
+        }
+        Self {
+            base: base % fast.modulus(),
+            modulus: fast.modulus(),
+            fast,
+            table,
+        }
+    }
+
+    pub fn base(&self) -> u64 {
+        self.base
+    }
+
+    pub fn modulus(&self) -> u64 {
+        self.modulus
+    }
+
+    /// `base^exp mod modulus`
+    pub fn pow(&self, exp: u64) -> u64 {
+        let mut result = self.table[0][(exp & 0xf) as usize];
+        for (i, row) in self.table.iter().enumerate().skip(1) {
+            let d = (exp >> (WINDOW * i as u32)) & 0xf;
+            if d != 0 {
+                result = self.fast.mul_repr(result, row[d as usize]);
+            }
+        }
+        self.fast.decode(result)
+    }
+}
+
+/// `base^e mod modulo`を`exps`の各`e`について求める。`modulo == 0`のときは全て0
+pub fn mpow_batch(base: u64, exps: &[u64], modulo: u64) -> Vec<u64> {
+    let table = PowTable::new(base, modulo);
+    exps.iter().map(|&e| table.pow(e)).collect()
+}
+
+/// `n`の素数冪`p^count`ごとの前計算
+#[derive(Clone, Debug)]
+struct PrimePower {
+    count: u32,
+    /// `a`が`p`で割り切れる回数 (`count`で打ち切る)
+    valuation: u32,
+    table: PowTable,
+    /// λ(p^count)
+    lambda: FastModulus,
+    /// 中国剰余定理で合わせるときの係数。`coefficient ≡ 1 (mod p^count)`かつ他の素数冪では0
+    coefficient: u64,
+}
+
+/// `a`と`n`を固定して`a^(b^c) mod n`を繰り返し求める
+///
+/// 素因数分解と各素数冪の冪乗表を構築時に1度だけ作る。
+#[derive(Clone, Debug)]
+pub struct Solver {
+    n: u64,
+    prime_powers: Vec<PrimePower>,
+}
+
+impl Solver {
+    /// `n == 0`のときは、[`solve`](crate::solve)と同じく常に0を返す
+    pub fn new(a: u64, n: u64) -> Self {
+        Self::try_new(a, n).unwrap_or(Self { n, prime_powers: Vec::new() })
+    }
+
+    pub fn try_new(a: u64, n: u64) -> Result<Self, ModPowError> {
+        if n == 0 {
+            return Err(ModPowError::ZeroModulus);
+        }
+        let factorization = factorize(n);
+        let prime_powers = factorization
+            .iter()
+            .map(|&(p, count)| {
+                let ppcount = p.pow(count);
+                let a = a % ppcount;
+                let mut valuation = 0;
+                let mut rest = a;
+                while valuation < count && rest.is_multiple_of(p) {
+                    rest /= p;
+                    valuation += 1;
+                }
+                let lambda = crate::Factorization::from_iter([(p, count)]).carmichael_lambda();
+                let (coefficient, _) = crt(&[(1, ppcount), (0, n / ppcount)]).expect("prime powers are pairwise coprime");
+                PrimePower {
+                    count,
+                    valuation,
+                    table: PowTable::new(a, ppcount),
+                    lambda: FastModulus::new(lambda),
+                    coefficient,
+                }
+            })
+            .collect();
+        Ok(Self { n, prime_powers })
+    }
+
+    pub fn modulus(&self) -> u64 {
+        self.n
+    }
+
+    /// `a^(b^c) mod n`
+    pub fn solve(&self, b: u64, c: u64) -> u64 {
+        let exp_capped = tower::capped_pow(&b, &c, tower::CAP);
+        let n = self.n as u128;
+        self.prime_powers.iter().fold(0, |ans, pp| {
+            let residue = if pp.valuation == 0 {
+                // aとp^countは互いに素なので指数をλ(p^count)で落とせる
+                pp.table.pow(pp.lambda.pow(b, c))
+            } else if (pp.valuation as u64).saturating_mul(exp_capped) < pp.count as u64 {
+                pp.table.pow(exp_capped)
+            } else {
+                0
+            };
+            ((ans as u128 + residue as u128 * pp.coefficient as u128) % n) as u64
+        })
+    }
+}
+
+/// `a^(b^c) mod n`を`queries`の各`(b, c)`について求める。`n == 0`のときは全て0
+pub fn solve_batch(a: u64, queries: &[(u64, u64)], n: u64) -> Vec<u64> {
+    let solver = Solver::new(a, n);
+    queries.iter().map(|&(b, c)| solver.solve(b, c)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::{mpow, solve};
+
+    #[test]
+    fn pow_table() {
+        let mut rng = StdRng::seed_from_u64(38);
+        for n in [1, 2, 3, 4, 1 << 63, u64::MAX, u64::MAX - 1, u64::MAX - 58] {
+            for _ in 0..20 {
+                let base = rng.gen();
+                let table = PowTable::new(base, n);
+                for e in [0, 1, 15, 16, u64::MAX, rng.gen()] {
+                    assert_eq!(table.pow(e), mpow(base, e, n), "{base}^{e} mod {n}");
+                }
+            }
+        }
+        assert_eq!(mpow_batch(3, &[0, 1, 2, 100], 1000), [1, 3, 9, 1]);
+        assert!(PowTable::try_new(3, 0).is_err());
+        let table = PowTable::new(3, 0);
+        assert_eq!((table.pow(5), table.modulus()), (0, 0));
+        assert_eq!(mpow_batch(3, &[0, 1, u64::MAX], 0), [0, 0, 0]);
+    }
+
+    #[test]
+    fn solver_matches_solve() {
+        let mut rng = StdRng::seed_from_u64(380);
+        let moduli = [1, 2, 72, 1 << 40, 3u64.pow(40), 1_234_567_890, 998_244_353 * 6, u64::MAX];
+        for n in moduli {
+            for a in [0, 1, 2, 6, 12, n - 1, rng.gen()] {
+                let mut queries = vec![(0, 0), (1, u64::MAX), (u64::MAX, u64::MAX), (rng.gen(), rng.gen())];
+                queries.extend((0..50).map(|_| (rng.gen_range(0..100), rng.gen_range(0..100))));
+                let answers = solve_batch(a, &queries, n);
+                for (&(b, c), ans) in queries.iter().zip(answers) {
+                    assert_eq!(ans, solve(a, b, c, n), "{a}^({b}^{c}) mod {n}");
+                }
+            }
+        }
+        assert!(Solver::try_new(3, 0).is_err());
+        assert_eq!(Solver::new(3, 0).modulus(), 0);
+        assert_eq!(solve_batch(3, &[(0, 0), (u64::MAX, u64::MAX)], 0), [0, 0]);
+    }
+}
@@ -0,0 +1,132 @@
+//! 拡張ユークリッドの互除法と中国剰余定理
+
+use crate::{uint, CrtError, UInt};
+
+/// `a * x + b * y = g` (`g = gcd(a, b)`)となる`(g, x, y)`
+///
+/// `|x| <= max(b, 1)`, `|y| <= max(a, 1)`を満たす。
+pub fn ext_gcd(a: u64, b: u64) -> (u64, i128, i128) {
+    let (mut r0, mut r1) = (a as i128, b as i128);
+    let (mut x0, mut x1) = (1i128, 0i128);
+    let (mut y0, mut y1) = (0i128, 1i128);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (x0, x1) = (x1, x0 - q * x1);
+        (y0, y1) = (y1, y0 - q * y1);
+    }
+    (r0 as u64, x0, y0)
+}
+
+/// `a * x ≡ 1 (mod m)`となる`0 <= x < m`
+///
+/// `a`と`m`が互いに素でないか、`m == 0`のときは`None`
+pub fn mod_inverse(a: u64, m: u64) -> Option<u64> {
+    if m == 0 {
+        return None;
+    }
+    let (g, x, _) = ext_gcd(a % m, m);
+    (g == 1).then(|| x.rem_euclid(m as i128) as u64)
+}
+
+/// 連立合同式`x ≡ r_i (mod m_i)`を解き、`(x, lcm(m_i))`を返す
+///
+/// 法は互いに素でなくてもよい。空の入力に対しては`Ok((0, 1))`
+///
+/// 法が0のときを除き、矛盾する合同式があれば最小公倍数の大きさに関わらず
+/// [`CrtError::NoSolution`]になる。
+pub fn crt<T: UInt>(congruences: &[(T, T)]) -> Result<(T, T), CrtError> {
+    if congruences.iter().any(|(_, m)| m.is_zero()) {
+        return Err(CrtError::ZeroModulus);
+    }
+    let (mut r0, mut m0) = (T::zero(), T::one());
+    for (r1, m1) in congruences {
+        let r1 = r1.clone() % m1.clone();
+        // r0 + m0 * t ≡ r1 (mod m1) を t について解く
+        let g = uint::gcd(m0.clone(), m1.clone());
+        let r0_mod = r0.clone() % m1.clone();
+        let diff = if r1 >= r0_mod { r1 - r0_mod } else { m1.clone() - (r0_mod - r1) };
+        if !(diff.clone() % g.clone()).is_zero() {
+            return Err(CrtError::NoSolution);
+        }
+        let m1_g = m1.clone() / g.clone();
+        let inv = uint::inverse(&(m0.clone() / g.clone()), &m1_g).expect("m0 / g and m1 / g are coprime");
+        let t = (diff / g).mul_mod(&inv, &m1_g);
+        let Some(lcm) = m0.checked_mul(&m1_g) else {
+            return Err(if pairwise_consistent(congruences) { CrtError::Overflow } else { CrtError::NoSolution });
+        };
+        // r0 < m0, t < m1 / g なので lcm を超えない
+        r0 = r0 + m0 * t;
+        m0 = lcm;
+    }
+    Ok((r0, m0))
+}
+
+/// 全ての組で`r_i ≡ r_j (mod gcd(m_i, m_j))`か。これが解の存在と同値
+fn pairwise_consistent<T: UInt>(congruences: &[(T, T)]) -> bool {
+    congruences.iter().enumerate().all(|(i, (r1, m1))| {
+        congruences[i + 1..].iter().all(|(r2, m2)| {
+            let g = uint::gcd(m1.clone(), m2.clone());
+            r1.clone() % g.clone() == r2.clone() % g
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inverse() {
+        for m in 1..200u64 {
+            for a in 0..m {
+                let expected = (0..m).find(|&x| a * x % m == 1 % m);
+                assert_eq!(mod_inverse(a, m), expected, "{a}^-1 mod {m}");
+            }
+        }
+        let p = u64::MAX - 58;
+        let x = mod_inverse(u64::MAX, p).unwrap();
+        assert_eq!(u64::MAX as u128 * x as u128 % p as u128, 1);
+        assert_eq!(mod_inverse(3, 0), None);
+        let (g, x, y) = ext_gcd(u64::MAX, u64::MAX - 1);
+        assert_eq!((g, u64::MAX as i128 * x + (u64::MAX - 1) as i128 * y), (1, 1));
+    }
+
+    #[test]
+    fn non_coprime() {
+        for m1 in 1..=24u64 {
+            for m2 in 1..=24u64 {
+                let lcm = m1 * m2 / uint::gcd(m1, m2);
+                for r1 in 0..m1 {
+                    for r2 in 0..m2 {
+                        let expected = (0..lcm).find(|x| x % m1 == r1 && x % m2 == r2).map(|x| (x, lcm)).ok_or(CrtError::NoSolution);
+                        assert_eq!(crt(&[(r1, m1), (r2, m2)]), expected, "x≡{r1} mod {m1}, x≡{r2} mod {m2}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn large() {
+        let (p, q) = (u64::MAX - 58, 4_294_967_291);
+        assert_eq!(crt::<u64>(&[]), Ok((0, 1)));
+        assert_eq!(crt(&[(5u64, 0)]), Err(CrtError::ZeroModulus));
+        assert_eq!(crt(&[(1u64, 3), (2, 3), (5, 0)]), Err(CrtError::ZeroModulus));
+        // 解はあるが最小公倍数がu64に収まらない
+        assert_eq!(crt(&[(1, p), (1, q)]), Err(CrtError::Overflow));
+        // 最小公倍数があふれた後に矛盾する合同式があっても解なしと分かる
+        assert_eq!(crt(&[(1, p), (1, q), (0, 2), (1, 4)]), Err(CrtError::NoSolution));
+        assert_eq!(crt(&[(1, p), (2, q), (2, 3 * q)]), Err(CrtError::Overflow));
+        assert_eq!(crt(&[(1, p), (2, q), (3, 3 * q)]), Err(CrtError::NoSolution));
+        assert_eq!(crt(&[(3, 1 << 32), (3, 1 << 31), (3, 4_294_967_291)]), Ok((3, q << 32)));
+        assert_eq!(crt(&[(p - 1, p), (7, 1 << 20)]), Err(CrtError::Overflow));
+        let (x, m) = crt(&[(q - 1, q), (5, 1 << 31)]).unwrap();
+        assert_eq!((x % q, x % (1 << 31), m), (q - 1, 5, q << 31));
+        // u128では同じ合同式の解が求まる
+        let (x, m) = crt(&[(1, p as u128), (1, q as u128)]).unwrap();
+        assert_eq!((x, m), (1, p as u128 * q as u128));
+        let (x, m) = crt(&[(p as u128 - 1, p as u128), (7, 1 << 64)]).unwrap();
+        assert_eq!((x % p as u128, x % (1 << 64), m), (p as u128 - 1, 7, (p as u128) << 64));
+    }
+}
@@ -0,0 +1,146 @@
+//! 離散対数
+
+use std::collections::HashMap;
+
+use crate::{crt, factorize, multiplicative_order, uint::gcd, Dynamic, ModInt};
+
+/// `a^x ≡ b (mod n)`となる最小の`0 <= x < bound`をbaby-step giant-stepで求める
+///
+/// `a`と`n`が互いに素でないときは`None`
+pub fn bsgs(a: u64, b: u64, n: u64, bound: u64) -> Option<u64> {
+    if n == 0 || bound == 0 {
+        return None;
+    }
+    let modulus = Dynamic::new(n);
+    let (a, b) = (ModInt::new(a, modulus), ModInt::new(b, modulus));
+    let step = bound.isqrt() + u64::from(bound.isqrt().pow(2) != bound);
+    let mut baby = HashMap::with_capacity(step.min(1 << 20) as usize);
+    let mut cur = ModInt::one(modulus);
+    for j in 0..step {
+        baby.entry(cur.value()).or_insert(j);
+        cur *= a;
+    }
+    // cur = a^step
+    let giant = cur.inv()?;
+    let mut gamma = b;
+    for i in 0..step {
+        if let Some(&j) = baby.get(&gamma.value()) {
+            let x = i * step + j;
+            return (x < bound).then_some(x);
+        }
+        gamma *= giant;
+    }
+    None
+}
+
+/// 位数`q^e`の巡回群での離散対数 (Pohlig–Hellman)
+fn dlog_prime_power(a: ModInt<Dynamic>, b: ModInt<Dynamic>, q: u64, e: u32) -> Option<u64> {
+    let n = a.modulus();
+    // 位数qの元
+    let gamma = a.pow(q.pow(e - 1));
+    let a_inv = a.inv()?;
+    let mut x = 0;
+    let mut qi = 1;
+    for i in 0..e {
+        let h = (a_inv.pow(x) * b).pow(q.pow(e - 1 - i));
+        let d = bsgs(gamma.value(), h.value(), n, q)?;
+        x += d * qi;
+        qi = qi.saturating_mul(q);
+    }
+    Some(x)
+}
+
+/// `a`と`n`が互いに素なときの`a^x ≡ b (mod n)`の最小解
+fn dlog_coprime(a: u64, b: u64, n: u64) -> Option<u64> {
+    let order = multiplicative_order(a, n)?;
+    let modulus = Dynamic::new(n);
+    let (a, b) = (ModInt::new(a, modulus), ModInt::new(b, modulus));
+    let mut congruences = Vec::new();
+    for &(q, e) in &factorize(order) {
+        let qe = q.pow(e);
+        let x = dlog_prime_power(a.pow(order / qe), b.pow(order / qe), q, e)?;
+        congruences.push((x, qe));
+    }
+    // 法は位数の素因数分解なので、最小公倍数は位数に収まる
+    let (x, _) = crt(&congruences).ok()?;
+    // bが<a>に含まれないときは各成分の解が合わない
+    (a.pow(x) == b).then_some(x)
+}
+
+/// `a^x ≡ b (mod n)`となる最小の`x >= 0`
+///
+/// `a`と`n`が互いに素でなくてもよい。`0^0 = 1`とする。
+pub fn discrete_log(a: u64, b: u64, n: u64) -> Option<u64> {
+    if n == 0 {
+        return None;
+    }
+    let modulus = Dynamic::new(n);
+    let (a, b) = (ModInt::new(a, modulus), ModInt::new(b, modulus));
+    // a^xの列は高々63項目から周期的になるので、それまでは直接調べる
+    const PREFIX: u64 = 64;
+    let mut cur = ModInt::one(modulus);
+    for x in 0..PREFIX {
+        if cur == b {
+            return Some(x);
+        }
+        cur *= a;
+    }
+    // n = n1 * n2 (n1はaと共通の素因数のみ、n2はaと互いに素) と分けると、
+    // x >= 64 では a^x ≡ 0 (mod n1)
+    let mut n2 = n;
+    loop {
+        let g = gcd(n2, a.value());
+        if g == 1 {
+            break;
+        }
+        while n2.is_multiple_of(g) {
+            n2 /= g;
+        }
+    }
+    let n1 = n / n2;
+    if !b.value().is_multiple_of(n1) {
+        return None;
+    }
+    let y = dlog_coprime(a.value() % n2, b.value() % n2, n2)?;
+    let order = multiplicative_order(a.value() % n2, n2)?;
+    // x ≡ y (mod order) かつ x >= PREFIX となる最小のx
+    let x = if y >= PREFIX { y } else { y + (PREFIX - y).div_ceil(order) * order };
+    Some(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpow;
+
+    fn brute_force(a: u64, b: u64, n: u64) -> Option<u64> {
+        (0..n + 64).find(|&x| mpow(a, x, n) == b % n)
+    }
+
+    #[test]
+    fn small() {
+        for n in 1..=80 {
+            for a in 0..n {
+                for b in 0..n {
+                    assert_eq!(discrete_log(a, b, n), brute_force(a, b, n), "{a}^x ≡ {b} mod {n}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn large() {
+        let p = 1_000_000_007;
+        let x = 123_456_789;
+        assert_eq!(discrete_log(5, mpow(5, x, p), p), Some(x));
+        // p - 1 = 2^23 * 7 * 17 なのでPohlig–Hellmanが効く
+        let p = 998_244_353;
+        assert_eq!(discrete_log(3, mpow(3, x, p), p), Some(x));
+        let n = 1 << 40;
+        assert_eq!(discrete_log(2, 0, n), Some(40));
+        assert_eq!(discrete_log(6, mpow(6, 1000, 1 << 50), 1 << 50), Some(50));
+        assert_eq!(discrete_log(6, 3, 1 << 50), None);
+        assert_eq!(bsgs(2, 3, 7, 100), None);
+        assert_eq!(bsgs(3, 1, 7, 100), Some(0));
+    }
+}
@@ -0,0 +1,52 @@
+//! 失敗しうる計算のエラー
+
+use std::fmt;
+
+/// `try_`で始まる関数や`try_new`が返すエラー
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ModPowError {
+    /// 法が0
+    ZeroModulus,
+    /// モンゴメリ乗算の法が偶数
+    EvenModulus(u64),
+    /// `value`と法`modulus`が互いに素でなく、逆元がない
+    NotInvertible { value: u64, modulus: u64 },
+    /// 篩の表を確保できない。`len`は篩う整数の個数
+    SieveTooLarge { len: u128 },
+}
+
+impl fmt::Display for ModPowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ZeroModulus => write!(f, "modulus must be nonzero"),
+            Self::EvenModulus(n) => write!(f, "Montgomery modulus must be odd, got {n}"),
+            Self::NotInvertible { value, modulus } => write!(f, "{value} is not invertible modulo {modulus}"),
+            Self::SieveTooLarge { len } => write!(f, "cannot allocate a sieve over {len} integers"),
+        }
+    }
+}
+
+impl std::error::Error for ModPowError {}
+
+/// [`crt`](crate::crt())のエラー
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CrtError {
+    /// 法が0
+    ZeroModulus,
+    /// 合同式が矛盾していて解がない
+    NoSolution,
+    /// 解はあるが、法の最小公倍数が型に収まらない
+    Overflow,
+}
+
+impl fmt::Display for CrtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ZeroModulus => write!(f, "modulus must be nonzero"),
+            Self::NoSolution => write!(f, "congruences have no common solution"),
+            Self::Overflow => write!(f, "lcm of moduli does not fit in the integer type"),
+        }
+    }
+}
+
+impl std::error::Error for CrtError {}
@@ -0,0 +1,232 @@
+use crate::{reduction::Montgomery, uint::gcd};
+
+const SMALL_PRIMES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/// `u64`全域で決定的なミラー・ラビン判定法
+pub fn is_prime(n: u64) -> bool {
+    for p in SMALL_PRIMES {
+        if n == p {
+            return true;
+        }
+        if n.is_multiple_of(p) {
+            return false;
+        }
+    }
+    if n < 41 * 41 {
+        return n > 1;
+    }
+    // 2^64未満ではこの7つの底で十分 (Jim Sinclair)
+    const BASES: [u64; 7] = [2, 325, 9375, 28178, 450775, 9780504, 1795265022];
+    let mont = Montgomery::new(n);
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    let one = mont.to_mont(1);
+    let minus_one = mont.to_mont(n - 1);
+    'base: for a in BASES {
+        let a = a % n;
+        if a == 0 {
+            continue;
+        }
+        let mut x = mont.pow_mont(mont.to_mont(a), d);
+        if x == one || x == minus_one {
+            continue;
+        }
+        for _ in 1..s {
+            x = mont.mul_mont(x, x);
+            if x == minus_one {
+                continue 'base;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+/// 奇数の合成数`n`の非自明な約数を1つ求める (Pollard's rho, Brent's variant)
+fn find_factor(n: u64) -> u64 {
+    debug_assert!(n & 1 == 1 && !is_prime(n));
+    let mont = Montgomery::new(n);
+    let add = |a: u64, b: u64| {
+        let (s, overflow) = a.overflowing_add(b);
+        if overflow || s >= n {
+            s.wrapping_sub(n)
+        } else {
+            s
+        }
+    };
+    // gcdをまとめて取る間隔
+    const M: u64 = 128;
+    for c in 1.. {
+        let c = mont.to_mont(c);
+        let f = |x: u64| add(mont.mul_mont(x, x), c);
+        let mut y = mont.to_mont(2);
+        let mut x = y;
+        let mut ys = y;
+        let mut q = mont.to_mont(1);
+        let mut g = 1;
+        let mut r = 1;
+        while g == 1 {
+            x = y;
+            for _ in 0..r {
+                y = f(y);
+            }
+            let mut k = 0;
+            while k < r && g == 1 {
+                ys = y;
+                for _ in 0..M.min(r - k) {
+                    y = f(y);
+                    q = mont.mul_mont(q, x.abs_diff(y));
+                }
+                // モンゴメリ表現でもRとnが互いに素なのでgcdは変わらない
+                g = gcd(q, n);
+                k += M;
+            }
+            r <<= 1;
+        }
+        if g == n {
+            // まとめすぎて全ての因数を同時に拾ったので1歩ずつやり直す
+            loop {
+                ys = f(ys);
+                g = gcd(x.abs_diff(ys), n);
+                if g > 1 {
+                    break;
+                }
+            }
+        }
+        if g != n {
+            return g;
+        }
+    }
+    unreachable!()
+}
+
+fn factorize_into(n: u64, primes: &mut Vec<u64>) {
+    if n == 1 {
+        return;
+    }
+    if is_prime(n) {
+        primes.push(n);
+        return;
+    }
+    let d = find_factor(n);
+    factorize_into(d, primes);
+    factorize_into(n / d, primes);
+}
+
+/// `n`の素因数を重複込みで`primes`に追加する。順序は問わない
+///
+/// [`UInt::factor_into`](crate::UInt::factor_into)の`u64`版。`n`が0のときは何も追加しない。
+pub(crate) fn factor_into(mut n: u64, primes: &mut Vec<u64>) {
+    if n == 0 {
+        return;
+    }
+    for p in SMALL_PRIMES {
+        while n.is_multiple_of(p) {
+            n /= p;
+            primes.push(p);
+        }
+    }
+    factorize_into(n, primes);
+}
+
+/// 素因数分解の結果。素因数の昇順に`(素数, 指数)`を持つ
+///
+/// `FromIterator`で任意の組から作れるが、`u64`に収まらない値を表すときは各メソッドの結果が飽和する。
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Factorization(Vec<(u64, u32)>);
+
+impl Factorization {
+    pub fn iter(&self) -> std::slice::Iter<'_, (u64, u32)> {
+        self.0.iter()
+    }
+
+    pub fn primes(&self) -> impl Iterator<Item = u64> + '_ {
+        self.0.iter().map(|&(p, _)| p)
+    }
+
+    /// `p`の指数。`p`が素因数でなければ0
+    pub fn exponent(&self, p: u64) -> u32 {
+        self.0
+            .binary_search_by_key(&p, |&(q, _)| q)
+            .map_or(0, |i| self.0[i].1)
+    }
+
+    /// 素因数分解された元の値。`u64`に収まらなければ`u64::MAX`
+    pub fn value(&self) -> u64 {
+        self.0.iter().fold(1, |n, &(p, k)| n.saturating_mul(p.saturating_pow(k)))
+    }
+}
+
+/// `p < 2`や`k == 0`の組は無視する
+impl Extend<(u64, u32)> for Factorization {
+    fn extend<T: IntoIterator<Item = (u64, u32)>>(&mut self, iter: T) {
+        for (p, k) in iter.into_iter().filter(|&(p, k)| p >= 2 && k > 0) {
+            match self.0.binary_search_by_key(&p, |&(q, _)| q) {
+                Ok(i) => self.0[i].1 = self.0[i].1.saturating_add(k),
+                Err(i) => self.0.insert(i, (p, k)),
+            }
+        }
+    }
+}
+
+impl FromIterator<(u64, u32)> for Factorization {
+    fn from_iter<T: IntoIterator<Item = (u64, u32)>>(iter: T) -> Self {
+        let mut factorization = Self::default();
+        factorization.extend(iter);
+        factorization
+    }
+}
+
+impl<'a> IntoIterator for &'a Factorization {
+    type Item = &'a (u64, u32);
+    type IntoIter = std::slice::Iter<'a, (u64, u32)>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+pub fn factorize(n: u64) -> Factorization {
+    let mut factorization = Factorization::default();
+    crate::prime_factors(n, &mut factorization);
+    factorization
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prime_factors;
+
+    fn is_prime_naive(n: u64) -> bool {
+        n >= 2 && (2..).take_while(|i| i * i <= n).all(|i| !n.is_multiple_of(i))
+    }
+
+    #[test]
+    fn primality() {
+        for n in 0..100_000 {
+            assert_eq!(is_prime(n), is_prime_naive(n), "{n}");
+        }
+        assert!(is_prime(u64::MAX - 58));
+        assert!(is_prime(1_000_000_007));
+        assert!(!is_prime(u64::MAX));
+        // 2, 3, 5, 7を底とする強擬素数
+        assert!(!is_prime(3_215_031_751));
+    }
+
+    #[test]
+    fn factorization() {
+        let cases: [(u64, &[(u64, u32)]); 7] = [
+            (1, &[]),
+            (2, &[(2, 1)]),
+            (720, &[(2, 4), (3, 2), (5, 1)]),
+            (u64::MAX, &[(3, 1), (5, 1), (17, 1), (257, 1), (641, 1), (65537, 1), (6700417, 1)]),
+            (4_294_967_291 * 4_294_967_279, &[(4_294_967_279, 1), (4_294_967_291, 1)]),
+            (1 << 63, &[(2, 63)]),
+            (999_983 * 999_983 * 1_000_003, &[(999_983, 2), (1_000_003, 1)]),
+        ];
+        for (n, expected) in cases {
+            let mut factors = Vec::new();
+            prime_factors(n, &mut factors);
+            assert_eq!(factors, expected, "{n}");
+        }
+    }
+}
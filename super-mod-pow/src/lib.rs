@@ -1,95 +1,206 @@
-use std::collections::HashMap;
+//! 冪乗剰余と数論の関数
+//!
+//! 公開関数は`u64`のどんな入力でもパニックしない。法が0のときは0を返し、区別したいときは
+//! `try_`で始まる版が[`ModPowError::ZeroModulus`]を返す。
+//!
+//! ただし次は値を返しようがないのでパニックし、それぞれ`try_`で始まる版がある。
+//!
+//! - 法そのものを表す型を0から作るとき ([`Dynamic::new`], [`FastModulus::new`]など)
+//! - 篩の表を確保できないとき ([`sieve::Sieve::new`]など)
+//! - [`ModInt`]の`/`で、割る数が法と互いに素でないとき ([`ModInt::try_div`])
 
-fn gcd(mut a: u64, mut b: u64) -> u64 {
-    while b != 0 {
-        let r = a % b;
-        a = b;
-        b = r;
+pub mod arith;
+pub mod batch;
+pub mod crt;
+pub mod dlog;
+pub mod error;
+pub mod factor;
+pub mod modint;
+pub mod reduction;
+pub mod root;
+pub mod sieve;
+pub mod tower;
+pub mod uint;
+
+pub use arith::{carmichael_lambda, divisors, euler_phi, multiplicative_order, primitive_root};
+pub use batch::{mpow_batch, solve_batch, PowTable, Solver};
+pub use crt::{crt, ext_gcd, mod_inverse};
+pub use dlog::{bsgs, discrete_log};
+pub use error::{CrtError, ModPowError};
+pub use factor::{factorize, is_prime, Factorization};
+pub use modint::{Const, Dynamic, DynamicModInt, ModInt, Modulus, StaticModInt};
+pub use reduction::{mpow_fast, FastModulus};
+pub use root::{kth_root, sqrt_mod};
+pub use tower::{tower_mod, try_tower_mod};
+pub use uint::{prime_factors, UInt};
+
+/// `base^exp mod modulo`。`modulo == 0`のときは0
+pub fn mpow<T: UInt>(base: T, exp: T, modulo: T) -> T {
+    base.pow_mod(&exp, &modulo)
+}
+
+/// `modulo == 0`を[`ModPowError::ZeroModulus`]として区別する[`mpow`]
+pub fn try_mpow<T: UInt>(base: T, exp: T, modulo: T) -> Result<T, ModPowError> {
+    if modulo.is_zero() {
+        return Err(ModPowError::ZeroModulus);
     }
-    a
+    Ok(base.pow_mod(&exp, &modulo))
 }
 
-pub fn prime_factors<M: Extend<(u64, u32)>>(mut n: u64, map: &mut M) {
-    let mut count2 = 0;
-    while n % 2 == 0 {
-        n /= 2;
-        count2 += 1;
+/// `a^(b^c) mod n`
+///
+/// `n = 0`と`n = 1`のときは常に0、`0^0 = 1`とする。
+pub fn solve<T: UInt>(a: T, b: T, c: T, n: T) -> T {
+    try_solve(a, b, c, n).unwrap_or_else(|_| T::zero())
+}
+
+/// `n == 0`を[`ModPowError::ZeroModulus`]として区別する[`solve`]
+pub fn try_solve<T: UInt>(a: T, b: T, c: T, n: T) -> Result<T, ModPowError> {
+    if n.is_zero() {
+        return Err(ModPowError::ZeroModulus);
     }
-    if count2 > 0 {
-        map.extend(std::iter::once((2, count2)));
+    let mut factors = Vec::new();
+    prime_factors(n.clone(), &mut factors);
+    // 素数冪 p^count ごとに a^(b^c) を求めて中国剰余定理で合わせる
+    let congruences = factors
+        .into_iter()
+        .map(|(p, count)| {
+            let ppcount = num_traits::pow(p.clone(), count as usize);
+            let a = a.clone() % ppcount.clone();
+            let residue = if (a.clone() % p.clone()).is_zero() {
+                // compare k * b^c and count
+                let mut k = 0;
+                let mut rest = a.clone();
+                while k < count && (rest.clone() % p.clone()).is_zero() {
+                    rest = rest / p.clone();
+                    k += 1;
+                }
+                let exp_capped = tower::capped_pow(&b, &c, count as u64);
+                if (k as u64).saturating_mul(exp_capped) < count as u64 {
+                    mpow(a, T::from(exp_capped as u32), ppcount.clone())
+                } else {
+                    T::zero()
+                }
+            } else {
+                // aとp^countは互いに素なので指数をλ(p^count)で落とせる
+                let lambda = arith::carmichael_lambda_prime_power(&p, count).expect("λ(p^count) < p^count");
+                mpow(a, mpow(b.clone(), c.clone(), lambda), ppcount.clone())
+            };
+            (residue, ppcount)
+        })
+        .collect::<Vec<_>>();
+    // 法は互いに素で、最小公倍数はnなので失敗しない
+    let (ans, modulo) = crt(&congruences).expect("prime powers of n are pairwise coprime");
+    debug_assert!(modulo == n);
+    Ok(ans)
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+    use proptest::prelude::*;
+
+    use super::*;
+
+    /// `b^c`を多倍長整数で正確に求めてから`a^(b^c) mod n`を計算する
+    fn reference(a: u64, b: u64, c: u64, n: u64) -> u64 {
+        let exp = BigUint::from(b).pow(c as u32);
+        let ans = BigUint::from(a).modpow(&exp, &BigUint::from(n));
+        u64::try_from(ans).unwrap()
     }
-    let mut i = 3;
-    while i * i <= n {
-        let mut count = 0;
-        while n % i == 0 {
-            n /= i;
-            count += 1;
-        }
-        if count > 0 {
-            map.extend(std::iter::once((i, count)));
-        }
-        i += 2;
+
+    /// 小さい素数の冪の積で、素因数が重複しやすい法
+    fn smooth_modulus() -> impl Strategy<Value = u64> {
+        prop::collection::vec((prop::sample::select(vec![2u64, 3, 5, 7, 11, 13]), 1u32..=6), 1..=4)
+            .prop_map(|factors| factors.iter().fold(1u64, |n, &(p, k)| n.saturating_mul(p.pow(k))))
+            .prop_filter("modulus too large", |&n| n < u64::MAX)
     }
-    if n > 1 {
-        map.extend(std::iter::once((n, 1)));
+
+    fn modulus() -> impl Strategy<Value = u64> {
+        prop_oneof![1u64..=1_000_000_000, smooth_modulus(), 1u64..=u64::MAX]
+    }
+
+    /// `gcd(a, n) > 1`になりやすい底
+    fn base(n: u64) -> impl Strategy<Value = u64> {
+        let divisors = divisors(n);
+        prop_oneof![
+            1u64..=1_000_000_000,
+            (prop::sample::select(divisors), 1u64..=1_000).prop_map(|(d, k)| d.saturating_mul(k)),
+            any::<u64>(),
+        ]
+    }
+
+    fn exponent() -> impl Strategy<Value = (u64, u64)> {
+        prop_oneof![
+            (1u64..=1_000_000_000, 1u64..=8),
+            (1u64..=64, 1u64..=40),
+            // k * b^c の比較ループで`tmp`がオーバーフローする大きさ
+            (1u64 << 58..=u64::MAX, 1u64..=3),
+        ]
     }
-}
 
-pub fn mpow(mut base: u64, mut exp: u64, modulo: u64) -> u64 {
-    let mut result = 1;
-    while exp > 0 {
-        if exp & 1 != 0 {
-            result = result * base % modulo;
+    /// `u128`の法でも多倍長整数の結果と一致する
+    #[test]
+    fn solve_u128() {
+        let p = (1u128 << 64) + 13;
+        for (a, b, c, n) in [(3, 5, 7, p), (6, 3, 3, p * 6 * 6), (p + 2, 10, 4, p * 1_000_000_007), (2, 2, 8, 1 << 100)] {
+            let exp = BigUint::from(b).pow(c as u32);
+            let expected = BigUint::from(a).modpow(&exp, &BigUint::from(n));
+            assert_eq!(BigUint::from(solve(a, b, c, n)), expected, "{a}^({b}^{c}) mod {n}");
         }
-        base = base * base % modulo;
-        exp >>= 1;
     }
-    result
-}
 
-pub fn solve(a: u64, b: u64, c: u64, n: u64) -> u64 {
-    let g = gcd(n, a);
-    // println!("{n} = {} * {}", g, n / g);
-    // println!("{a} = {} * {}", g, a / g);
-    // let p = eratosthenes(n);
-    // println!("{:?}", p);
-    let mut n_prime_factors = HashMap::new();
-    let mut p = HashMap::new();
-    prime_factors(g, &mut p);
-    prime_factors(n, &mut n_prime_factors);
-    let mut ans = 1u64;
-
-    let mut phi = 1;
-    for (&p, &k) in &n_prime_factors {
-        phi *= p.pow(k - 1) * (p - 1);
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn solve_bigint() {
+        let n = BigUint::from(10u32).pow(40) + 7u32;
+        let (a, b, c) = (BigUint::from(123_456_789u32), BigUint::from(1000u32), BigUint::from(3u32));
+        let expected = a.modpow(&b.pow(3), &n);
+        assert_eq!(solve(a, b, c, n), expected);
     }
 
-    for (&p, &k) in &p {
-        let count = n_prime_factors[&p];
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(2000))]
 
-        // compare k * b^c and count
-        {
-            let mut tmp = k as u64;
-            for _ in 0..c {
-                if tmp > count as u64 {
-                    break;
-                }
-                tmp *= b;
-            }
-            if tmp < count as u64 {
-                ans = ans * mpow(p, tmp, n) % n;
-                continue;
-            }
+        #[test]
+        fn solve_matches_reference(
+            (a, n) in modulus().prop_flat_map(|n| (base(n), Just(n))),
+            (b, c) in exponent(),
+        ) {
+            prop_assert_eq!(solve(a, b, c, n), reference(a, b, c, n));
+        }
+
+        /// 参照実装で扱えない大きなcは`tower_mod`と比べる
+        #[test]
+        fn solve_matches_tower(
+            (a, n) in modulus().prop_flat_map(|n| (base(n), Just(n))),
+            b in 1u64..=1_000_000_000,
+            c in 1u64..=1_000_000_000_000_000_000,
+        ) {
+            prop_assert_eq!(solve(a, b, c, n), tower_mod(&[a, b, c], n));
         }
-        let ppcount = p.pow(count);
-        let modulo = n / ppcount;
+    }
 
-        // calculate p^(k * b^c - count) (mod modulo)
-        let phi = phi / (ppcount / p * (p - 1));
-        let exp = (mpow(b, c, phi) + phi - count as u64) % phi;
-        ans = ans * (mpow(p, exp, modulo) * ppcount) % n;
+    #[test]
+    fn solve_edge_cases() {
+        for (a, b, c, n) in [(4, 1, 1, 2), (12, 2, 1, 8), (2, 1, 1, 4), (6, 3, 2, 72), (1, 5, 5, 1), (0, 0, 3, 5)] {
+            assert_eq!(solve(a, b, c, n), reference(a, b, c, n), "{a}^({b}^{c}) mod {n}");
+        }
+        // b = 1 のとき比較ループがc回まわっていた
+        assert_eq!(solve(2u64, 1, 1_000_000_000_000_000_000, 8), 2);
+        assert_eq!(solve(6u32, 3, 2, 72), 0);
+        assert_eq!(try_solve(5u64, 0, 0, 1), Ok(0));
+        assert_eq!(try_solve(5u64, 3, 3, 0), Err(ModPowError::ZeroModulus));
+        assert_eq!(try_mpow(5u128, 3, 0), Err(ModPowError::ZeroModulus));
+        assert_eq!(try_tower_mod(&[2, 3], 0), Err(ModPowError::ZeroModulus));
+        // 法が0でもパニックせず0を返す
+        assert_eq!(solve(5u64, 3, 3, 0), 0);
+        assert_eq!(mpow(5u32, 3, 0), 0);
+        assert_eq!(mpow(5u64, 3, 0), 0);
+        assert_eq!(mpow(5u128, 3, 0), 0);
+        assert_eq!(mpow_fast(5, 3, 0), 0);
+        assert_eq!(tower_mod(&[2, 3], 0), 0);
+        assert_eq!(solve(3u32, 2, 2, 4_000_000_007), 81);
+        assert_eq!(solve(100000u64, 234556873, 849928198883232, 1234567890), tower_mod(&[100000, 234556873, 849928198883232], 1234567890));
     }
-    let a = a / g;
-    ans = ans * mpow(a, mpow(b, c, phi), n) % n;
-    ans
 }
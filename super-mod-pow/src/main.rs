@@ -1,60 +1,263 @@
-use std::ops::Deref;
+use std::{
+    fmt,
+    io::{self, BufRead, Write},
+    process::ExitCode,
+};
 
-#[allow(unused_imports)]
-use proconio::input;
-use rand::Rng;
+use super_mod_pow::{crt, discrete_log, euler_phi, factorize, is_prime, mpow, tower_mod, CrtError};
 
+const USAGE: &str = "\
+usage: super-mod-pow [--json] [COMMAND ARGS...]
 
+コマンドを省略すると標準入力から1行1クエリで読み込む
 
-fn eratosthenes(n: u64) -> Vec<u64> {
-    let mut is_prime = vec![true; n as usize + 1];
-    is_prime[0] = false;
-    is_prime[1] = false;
-    let mut p = 2;
-    while p * p <= n {
-        if is_prime[p as usize] {
-            let mut i = p * p;
-            while i <= n {
-                is_prime[i as usize] = false;
-                i += p;
-            }
+commands:
+    pow a e n               a^e mod n
+    tower a1 a2 ... ak n    a1^(a2^(...^ak)) mod n
+    factor n                素因数分解
+    phi n                   オイラーのφ関数
+    isprime n               素数判定
+    dlog a b n              a^x ≡ b (mod n) となる最小のx
+    crt r1 m1 r2 m2 ...     x ≡ r_i (mod m_i) となる最小のx と lcm(m_i)";
+
+#[derive(Debug, PartialEq, Eq)]
+enum Output {
+    Int(u64),
+    Bool(bool),
+    Factors(Vec<(u64, u32)>),
+    Crt(u64, u64),
+    NoSolution,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum QueryError {
+    UnknownCommand(String),
+    WrongArity { command: &'static str, expected: &'static str },
+    InvalidNumber(String),
+    ZeroModulus,
+    Overflow,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownCommand(command) => write!(f, "unknown command `{command}`"),
+            Self::WrongArity { command, expected } => write!(f, "`{command}` takes {expected}"),
+            Self::InvalidNumber(s) => write!(f, "`{s}` is not a non-negative 64-bit integer"),
+            Self::ZeroModulus => write!(f, "modulus must be positive"),
+            Self::Overflow => write!(f, "result does not fit in a 64-bit integer"),
         }
-        p += 1;
     }
-    is_prime.into_iter().enumerate().filter_map(|(i, b)| if b { Some(i as u64) } else { None }).collect()
 }
 
+fn nonzero(n: u64) -> Result<u64, QueryError> {
+    if n == 0 {
+        Err(QueryError::ZeroModulus)
+    } else {
+        Ok(n)
+    }
+}
+
+fn run(command: &str, args: &[&str]) -> Result<Output, QueryError> {
+    let args = args
+        .iter()
+        .map(|s| s.parse::<u64>().map_err(|_| QueryError::InvalidNumber(s.to_string())))
+        .collect::<Result<Vec<_>, _>>()?;
+    let arity = |command, expected| QueryError::WrongArity { command, expected };
+    let output = match command {
+        "pow" => {
+            let &[a, e, n] = args.as_slice() else {
+                return Err(arity("pow", "3 arguments"));
+            };
+            Output::Int(mpow(a, e, nonzero(n)?))
+        }
+        "tower" => {
+            let Some((&n, tower)) = args.split_last() else {
+                return Err(arity("tower", "at least 1 argument"));
+            };
+            Output::Int(tower_mod(tower, nonzero(n)?))
+        }
+        "factor" => {
+            let &[n] = args.as_slice() else {
+                return Err(arity("factor", "1 argument"));
+            };
+            Output::Factors(factorize(nonzero(n)?).iter().copied().collect())
+        }
+        "phi" => {
+            let &[n] = args.as_slice() else {
+                return Err(arity("phi", "1 argument"));
+            };
+            Output::Int(euler_phi(nonzero(n)?))
+        }
+        "isprime" => {
+            let &[n] = args.as_slice() else {
+                return Err(arity("isprime", "1 argument"));
+            };
+            Output::Bool(is_prime(n))
+        }
+        "dlog" => {
+            let &[a, b, n] = args.as_slice() else {
+                return Err(arity("dlog", "3 arguments"));
+            };
+            discrete_log(a, b, nonzero(n)?).map_or(Output::NoSolution, Output::Int)
+        }
+        "crt" => {
+            if args.len() % 2 != 0 {
+                return Err(arity("crt", "pairs of residue and modulus"));
+            }
+            let congruences = args
+                .chunks_exact(2)
+                .map(|c| Ok((c[0], nonzero(c[1])?)))
+                .collect::<Result<Vec<_>, _>>()?;
+            match crt(&congruences) {
+                Ok((x, m)) => Output::Crt(x, m),
+                Err(CrtError::NoSolution) => Output::NoSolution,
+                Err(CrtError::ZeroModulus) => return Err(QueryError::ZeroModulus),
+                Err(CrtError::Overflow) => return Err(QueryError::Overflow),
+            }
+        }
+        _ => return Err(QueryError::UnknownCommand(command.to_string())),
+    };
+    Ok(output)
+}
+
+fn json_string(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len() + 2);
+    escaped.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
 
-fn is_prime(n: u64) -> bool {
-    match n {
-        0 | 1 => return false,
-        2 => return true,
-        _ if n % 2 == 0 => return false,
-        _ => (),
+fn format_plain(result: &Result<Output, QueryError>) -> String {
+    match result {
+        Ok(Output::Int(n)) => n.to_string(),
+        Ok(Output::Bool(b)) => b.to_string(),
+        Ok(Output::Factors(factors)) => factors
+            .iter()
+            .map(|&(p, k)| if k == 1 { p.to_string() } else { format!("{p}^{k}") })
+            .collect::<Vec<_>>()
+            .join(" "),
+        Ok(Output::Crt(x, m)) => format!("{x} {m}"),
+        Ok(Output::NoSolution) => "none".to_string(),
+        Err(e) => format!("error: {e}"),
     }
-    let mut i = 3;
-    while i * i <= n {
-        if n % i == 0 {
-            return false;
+}
+
+fn format_json(query: &[&str], result: &Result<Output, QueryError>) -> String {
+    let query = query.iter().map(|s| json_string(s)).collect::<Vec<_>>().join(",");
+    let value = match result {
+        Ok(Output::Int(n)) => format!("\"result\":{n}"),
+        Ok(Output::Bool(b)) => format!("\"result\":{b}"),
+        Ok(Output::Factors(factors)) => {
+            let factors = factors.iter().map(|(p, k)| format!("[{p},{k}]")).collect::<Vec<_>>();
+            format!("\"result\":[{}]", factors.join(","))
         }
-        i += 2;
+        Ok(Output::Crt(x, m)) => format!("\"result\":{{\"x\":{x},\"modulus\":{m}}}"),
+        Ok(Output::NoSolution) => "\"result\":null".to_string(),
+        Err(e) => format!("\"error\":{}", json_string(&e.to_string())),
+    };
+    format!("{{\"query\":[{query}],{value}}}")
+}
+
+/// クエリを1つ処理して出力する。失敗したら`false`
+fn answer(query: &[&str], json: bool, out: &mut impl Write) -> io::Result<bool> {
+    let Some((command, args)) = query.split_first() else {
+        return Ok(true);
+    };
+    let result = run(command, args);
+    if json {
+        writeln!(out, "{}", format_json(query, &result))?;
+    } else if result.is_ok() {
+        writeln!(out, "{}", format_plain(&result))?;
+    } else {
+        eprintln!("{}", format_plain(&result));
     }
-    true
+    Ok(result.is_ok())
 }
 
-fn check1() {
-    let mut rng = rand::thread_rng();
-    let cases = 1000;
-    let a = 1;
-    let b = std::iter::repeat_with(|| rng.gen_range(1..=1_000_000_000)).take(cases).collect::<Vec<_>>();
-    let c = std::iter::repeat_with(|| rng.gen_range(1..=1_000_000_000_000_000_000)).take(cases).collect::<Vec<_>>();
-    let n = std::iter::repeat_with(|| rng.gen_range(2..=1_000_000_000)).take(cases).collect::<Vec<_>>();
-    for i in 0..cases {
-        assert_eq!(super_mod_pow::solve(a, b[i], c[i], n[i]), 1);
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let mut args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    if args.iter().any(|&a| a == "-h" || a == "--help") {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+    let json = args.first() == Some(&"--json");
+    if json {
+        args.remove(0);
+    }
+    let stdout = io::stdout();
+    let mut out = io::BufWriter::new(stdout.lock());
+    let mut ok = true;
+    let result = if !args.is_empty() {
+        answer(&args, json, &mut out).map(|success| ok &= success)
+    } else {
+        io::stdin().lock().lines().try_for_each(|line| {
+            let line = line?;
+            let query = line.split_whitespace().collect::<Vec<_>>();
+            ok &= answer(&query, json, &mut out)?;
+            out.flush()
+        })
+    };
+    if let Err(e) = result.and_then(|()| out.flush()) {
+        eprintln!("error: {e}");
+        return ExitCode::from(2);
+    }
+    if ok {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
     }
 }
 
-fn main() {
-    check1();
-    println!("{}", super_mod_pow::solve(100000, 234556873, 849928198883232, 1234567890));
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queries() {
+        assert_eq!(run("pow", &["2", "10", "1000"]), Ok(Output::Int(24)));
+        assert_eq!(run("tower", &["2", "3", "2", "1000"]), Ok(Output::Int(512)));
+        assert_eq!(run("tower", &["7"]), Ok(Output::Int(1)));
+        assert_eq!(run("factor", &["720"]), Ok(Output::Factors(vec![(2, 4), (3, 2), (5, 1)])));
+        assert_eq!(run("phi", &["720"]), Ok(Output::Int(192)));
+        assert_eq!(run("isprime", &["18446744073709551557"]), Ok(Output::Bool(true)));
+        assert_eq!(run("dlog", &["2", "3", "7"]), Ok(Output::NoSolution));
+        assert_eq!(run("crt", &["2", "3", "3", "5"]), Ok(Output::Crt(8, 15)));
+        assert_eq!(run("crt", &["0", "2", "1", "4"]), Ok(Output::NoSolution));
+    }
+
+    #[test]
+    fn invalid_input() {
+        assert_eq!(run("pow", &["2", "10", "0"]), Err(QueryError::ZeroModulus));
+        assert_eq!(run("crt", &["1", "0"]), Err(QueryError::ZeroModulus));
+        // 解はあるが最小公倍数が64bitに収まらない
+        assert_eq!(run("crt", &["1", "18446744073709551557", "1", "4294967291"]), Err(QueryError::Overflow));
+        assert_eq!(run("phi", &["-1"]), Err(QueryError::InvalidNumber("-1".to_string())));
+        assert_eq!(run("pow", &["1"]), Err(QueryError::WrongArity { command: "pow", expected: "3 arguments" }));
+        assert_eq!(run("sqrt", &[]), Err(QueryError::UnknownCommand("sqrt".to_string())));
+    }
+
+    #[test]
+    fn formatting() {
+        let query = ["factor", "12"];
+        let result = run(query[0], &query[1..]);
+        assert_eq!(format_plain(&result), "2^2 3");
+        assert_eq!(format_json(&query, &result), r#"{"query":["factor","12"],"result":[[2,2],[3,1]]}"#);
+        let result = run("crt", &["1", "2", "0", "2"]);
+        assert_eq!(format_json(&["crt"], &result), r#"{"query":["crt"],"result":null}"#);
+        let result = run("phi", &["x\""]);
+        assert_eq!(
+            format_json(&["phi"], &result),
+            r#"{"query":["phi"],"error":"`x\"` is not a non-negative 64-bit integer"}"#
+        );
+    }
 }
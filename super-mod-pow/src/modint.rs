@@ -0,0 +1,255 @@
+use std::{
+    fmt,
+    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+};
+
+use crate::ModPowError;
+
+/// `ModInt`の法を与える型
+pub trait Modulus: Copy {
+    fn modulus(self) -> u64;
+}
+
+/// コンパイル時定数の法
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Const<const M: u64>;
+
+impl<const M: u64> Const<M> {
+    const NONZERO: () = assert!(M != 0, "modulus must be nonzero");
+}
+
+impl<const M: u64> Modulus for Const<M> {
+    #[inline]
+    fn modulus(self) -> u64 {
+        #[allow(clippy::let_unit_value)]
+        let _ = Self::NONZERO;
+        M
+    }
+}
+
+/// 実行時に決まる法
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Dynamic(u64);
+
+impl Dynamic {
+    /// # Panics
+    /// `modulus == 0`のとき。パニックさせたくなければ[`Dynamic::try_new`]を使う
+    pub fn new(modulus: u64) -> Self {
+        Self::try_new(modulus).expect("modulus must be nonzero")
+    }
+
+    pub fn try_new(modulus: u64) -> Result<Self, ModPowError> {
+        if modulus == 0 {
+            return Err(ModPowError::ZeroModulus);
+        }
+        Ok(Self(modulus))
+    }
+}
+
+impl Modulus for Dynamic {
+    #[inline]
+    fn modulus(self) -> u64 {
+        self.0
+    }
+}
+
+/// `0..modulus`の範囲に正規化された剰余類
+///
+/// 乗算は`u128`に拡張して行うので、法が`u64`の範囲全体で正しく計算できる。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ModInt<M: Modulus> {
+    value: u64,
+    modulus: M,
+}
+
+pub type StaticModInt<const M: u64> = ModInt<Const<M>>;
+pub type DynamicModInt = ModInt<Dynamic>;
+
+impl<M: Modulus> ModInt<M> {
+    #[inline]
+    pub fn new(value: u64, modulus: M) -> Self {
+        Self {
+            value: value % modulus.modulus(),
+            modulus,
+        }
+    }
+
+    #[inline]
+    fn from_reduced(value: u64, modulus: M) -> Self {
+        debug_assert!(value < modulus.modulus());
+        Self { value, modulus }
+    }
+
+    #[inline]
+    pub fn value(self) -> u64 {
+        self.value
+    }
+
+    #[inline]
+    pub fn modulus(self) -> u64 {
+        self.modulus.modulus()
+    }
+
+    #[inline]
+    pub fn zero(modulus: M) -> Self {
+        Self::from_reduced(0, modulus)
+    }
+
+    #[inline]
+    pub fn one(modulus: M) -> Self {
+        Self::new(1, modulus)
+    }
+
+    pub fn pow(self, mut exp: u64) -> Self {
+        let mut base = self;
+        let mut result = Self::one(self.modulus);
+        while exp > 0 {
+            if exp & 1 != 0 {
+                result *= base;
+            }
+            base *= base;
+            exp >>= 1;
+        }
+        result
+    }
+
+    /// 逆元が存在しなければ`None`
+    pub fn inv(self) -> Option<Self> {
+        crate::mod_inverse(self.value, self.modulus()).map(|v| Self::from_reduced(v, self.modulus))
+    }
+
+    /// パニックしない`self / rhs`
+    pub fn try_div(self, rhs: Self) -> Result<Self, ModPowError> {
+        let inv = rhs.inv().ok_or(ModPowError::NotInvertible {
+            value: rhs.value,
+            modulus: rhs.modulus(),
+        })?;
+        Ok(self * inv)
+    }
+}
+
+impl<const M: u64> From<u64> for StaticModInt<M> {
+    fn from(value: u64) -> Self {
+        Self::new(value, Const)
+    }
+}
+
+impl<M: Modulus> fmt::Display for ModInt<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.value, f)
+    }
+}
+
+impl<M: Modulus> Add for ModInt<M> {
+    type Output = Self;
+    #[inline]
+    fn add(self, rhs: Self) -> Self::Output {
+        debug_assert_eq!(self.modulus(), rhs.modulus());
+        let m = self.modulus();
+        let (sum, overflow) = self.value.overflowing_add(rhs.value);
+        let value = if overflow || sum >= m { sum.wrapping_sub(m) } else { sum };
+        Self::from_reduced(value, self.modulus)
+    }
+}
+
+impl<M: Modulus> Sub for ModInt<M> {
+    type Output = Self;
+    #[inline]
+    fn sub(self, rhs: Self) -> Self::Output {
+        debug_assert_eq!(self.modulus(), rhs.modulus());
+        let value = if self.value >= rhs.value {
+            self.value - rhs.value
+        } else {
+            self.value.wrapping_sub(rhs.value).wrapping_add(self.modulus())
+        };
+        Self::from_reduced(value, self.modulus)
+    }
+}
+
+impl<M: Modulus> Mul for ModInt<M> {
+    type Output = Self;
+    #[inline]
+    fn mul(self, rhs: Self) -> Self::Output {
+        debug_assert_eq!(self.modulus(), rhs.modulus());
+        let value = (self.value as u128 * rhs.value as u128 % self.modulus() as u128) as u64;
+        Self::from_reduced(value, self.modulus)
+    }
+}
+
+impl<M: Modulus> Div for ModInt<M> {
+    type Output = Self;
+    /// # Panics
+    /// `rhs`が法と互いに素でないとき。パニックさせたくなければ[`ModInt::try_div`]を使う
+    fn div(self, rhs: Self) -> Self::Output {
+        self.try_div(rhs).expect("divisor is not invertible")
+    }
+}
+
+impl<M: Modulus> Neg for ModInt<M> {
+    type Output = Self;
+    #[inline]
+    fn neg(self) -> Self::Output {
+        Self::zero(self.modulus) - self
+    }
+}
+
+macro_rules! impl_assign {
+    ($($trait:ident::$method:ident => $op:ident::$op_method:ident),*) => {
+        $(
+            impl<M: Modulus> $trait for ModInt<M> {
+                #[inline]
+                fn $method(&mut self, rhs: Self) {
+                    *self = $op::$op_method(*self, rhs);
+                }
+            }
+        )*
+    };
+}
+
+impl_assign!(
+    AddAssign::add_assign => Add::add,
+    SubAssign::sub_assign => Sub::sub,
+    MulAssign::mul_assign => Mul::mul,
+    DivAssign::div_assign => Div::div
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn large_modulus() {
+        let m = Dynamic::new(u64::MAX - 58);
+        let a = ModInt::new(u64::MAX - 60, m);
+        let b = ModInt::new(u64::MAX - 59, m);
+        assert_eq!((a + b).value(), u64::MAX - 61);
+        assert_eq!((a - b).value(), u64::MAX - 59);
+        assert_eq!((a * b).value(), 2);
+        assert_eq!((-a).value(), 2);
+        assert_eq!((a / b * b), a);
+        assert_eq!(a.pow(u64::MAX - 59), ModInt::one(m));
+    }
+
+    #[test]
+    fn static_modulus() {
+        type Mint = StaticModInt<998_244_353>;
+        let a = Mint::from(3);
+        assert_eq!(a.pow(998_244_352), Mint::from(1));
+        assert_eq!(a.inv().unwrap() * a, Mint::from(1));
+        assert_eq!(Mint::from(998_244_354).value(), 1);
+    }
+
+    #[test]
+    fn non_invertible() {
+        let m = Dynamic::new(12);
+        assert_eq!(ModInt::new(8, m).inv(), None);
+        assert_eq!(ModInt::new(5, m).inv(), Some(ModInt::new(5, m)));
+        assert_eq!(ModInt::new(7, Dynamic::new(1)).pow(0).value(), 0);
+        assert_eq!(Dynamic::try_new(0), Err(ModPowError::ZeroModulus));
+        assert_eq!(ModInt::new(5, m).try_div(ModInt::new(7, m)), Ok(ModInt::new(11, m)));
+        assert_eq!(
+            ModInt::new(5, m).try_div(ModInt::new(8, m)),
+            Err(ModPowError::NotInvertible { value: 8, modulus: 12 })
+        );
+    }
+}
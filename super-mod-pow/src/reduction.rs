@@ -0,0 +1,124 @@
+mod barrett;
+mod montgomery;
+
+pub use barrett::Barrett;
+pub use montgomery::Montgomery;
+
+use crate::ModPowError;
+
+/// 法の偶奇に応じてモンゴメリ乗算かバレット還元を選ぶ
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FastModulus {
+    Montgomery(Montgomery),
+    Barrett(Barrett),
+}
+
+impl FastModulus {
+    /// # Panics
+    /// `n == 0`のとき。パニックさせたくなければ[`FastModulus::try_new`]を使う
+    pub fn new(n: u64) -> Self {
+        Self::try_new(n).expect("modulus must be nonzero")
+    }
+
+    pub fn try_new(n: u64) -> Result<Self, ModPowError> {
+        if n & 1 == 1 {
+            Montgomery::try_new(n).map(Self::Montgomery)
+        } else {
+            Barrett::try_new(n).map(Self::Barrett)
+        }
+    }
+
+    #[inline]
+    pub fn modulus(&self) -> u64 {
+        match self {
+            Self::Montgomery(m) => m.modulus(),
+            Self::Barrett(b) => b.modulus(),
+        }
+    }
+
+    #[inline]
+    pub fn mul_mod(&self, a: u64, b: u64) -> u64 {
+        match self {
+            Self::Montgomery(m) => m.mul_mod(a, b),
+            Self::Barrett(r) => r.mul_mod(a, b),
+        }
+    }
+
+    pub fn pow(&self, base: u64, exp: u64) -> u64 {
+        match self {
+            Self::Montgomery(m) => m.pow(base, exp),
+            Self::Barrett(b) => b.pow(base, exp),
+        }
+    }
+
+    /// 内部表現に変換する。モンゴメリ乗算ではモンゴメリ表現、バレット還元ではそのままの剰余
+    ///
+    /// 同じ法で何度も掛けるときは、内部表現のまま[`mul_repr`](Self::mul_repr)で掛けて最後に
+    /// [`decode`](Self::decode)で戻すと変換の分だけ速い。
+    #[inline]
+    pub fn encode(&self, a: u64) -> u64 {
+        match self {
+            Self::Montgomery(m) => m.to_mont(a),
+            Self::Barrett(b) => b.reduce(a as u128),
+        }
+    }
+
+    /// [`encode`](Self::encode)の逆
+    #[inline]
+    pub fn decode(&self, a: u64) -> u64 {
+        match self {
+            Self::Montgomery(m) => m.from_mont(a),
+            Self::Barrett(_) => a,
+        }
+    }
+
+    /// 内部表現同士の積
+    #[inline]
+    pub fn mul_repr(&self, a: u64, b: u64) -> u64 {
+        match self {
+            Self::Montgomery(m) => m.mul_mont(a, b),
+            Self::Barrett(r) => r.mul_mod(a, b),
+        }
+    }
+}
+
+/// `mpow`と同じ値を、[`FastModulus`]で計算する
+///
+/// 法ごとの前計算には除算を使うが、冪乗の途中の乗算では除算命令を使わない。
+/// `mpow`と同じく`modulo == 0`のときは0
+pub fn mpow_fast(base: u64, exp: u64, modulo: u64) -> u64 {
+    FastModulus::try_new(modulo).map_or(0, |m| m.pow(base, exp))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::mpow;
+
+    #[test]
+    fn agrees_with_mpow() {
+        let mut rng = StdRng::seed_from_u64(27);
+        let moduli = [1, 2, 3, 4, 1 << 63, u64::MAX, u64::MAX - 1, u64::MAX - 58];
+        let random = std::iter::repeat_with(|| rng.gen::<u64>()).take(200).collect::<Vec<_>>();
+        for &n in moduli.iter().chain(&random) {
+            let fast = FastModulus::new(n);
+            let barrett = Barrett::new(n);
+            for _ in 0..20 {
+                let (a, b, e) = (rng.gen::<u64>(), rng.gen::<u64>(), rng.gen::<u64>());
+                let expected = (a as u128 * b as u128 % n as u128) as u64;
+                assert_eq!(fast.mul_mod(a, b), expected);
+                assert_eq!(barrett.mul_mod(a, b), expected);
+                assert_eq!(fast.pow(a, e), mpow(a, e, n));
+                assert_eq!(barrett.pow(a, e), mpow(a, e, n));
+            }
+            assert_eq!(mpow_fast(n.wrapping_sub(1), 0, n), mpow(0, 0, n));
+            // 内部表現のまま掛けても同じ
+            let (a, b) = (rng.gen::<u64>(), rng.gen::<u64>());
+            let product = fast.mul_repr(fast.encode(a), fast.encode(b));
+            assert_eq!(fast.decode(product), fast.mul_mod(a, b));
+            assert_eq!(fast.decode(fast.encode(a)), (a as u128 % n as u128) as u64);
+        }
+    }
+}
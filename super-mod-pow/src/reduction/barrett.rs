@@ -0,0 +1,75 @@
+use crate::ModPowError;
+
+/// 任意の法に対するバレット還元
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Barrett {
+    n: u64,
+    /// floor((2^128 - 1) / n)
+    m: u128,
+}
+
+/// 128bit同士の積の上位128bit
+#[inline]
+fn mul_hi(a: u128, b: u128) -> u128 {
+    let (a1, a0) = (a >> 64, a as u64 as u128);
+    let (b1, b0) = (b >> 64, b as u64 as u128);
+    let p00 = a0 * b0;
+    let p01 = a0 * b1;
+    let p10 = a1 * b0;
+    let p11 = a1 * b1;
+    let mid = (p00 >> 64) + (p01 as u64 as u128) + (p10 as u64 as u128);
+    p11 + (p01 >> 64) + (p10 >> 64) + (mid >> 64)
+}
+
+impl Barrett {
+    /// # Panics
+    /// `n == 0`のとき。パニックさせたくなければ[`Barrett::try_new`]を使う
+    pub fn new(n: u64) -> Self {
+        Self::try_new(n).expect("modulus must be nonzero")
+    }
+
+    pub fn try_new(n: u64) -> Result<Self, ModPowError> {
+        if n == 0 {
+            return Err(ModPowError::ZeroModulus);
+        }
+        Ok(Self {
+            n,
+            m: u128::MAX / n as u128,
+        })
+    }
+
+    #[inline]
+    pub fn modulus(&self) -> u64 {
+        self.n
+    }
+
+    /// x mod n
+    #[inline]
+    pub fn reduce(&self, x: u128) -> u64 {
+        // 推定した商は真の商より高々2小さい
+        let q = mul_hi(x, self.m);
+        let mut r = x.wrapping_sub(q.wrapping_mul(self.n as u128));
+        while r >= self.n as u128 {
+            r -= self.n as u128;
+        }
+        r as u64
+    }
+
+    #[inline]
+    pub fn mul_mod(&self, a: u64, b: u64) -> u64 {
+        self.reduce(a as u128 * b as u128)
+    }
+
+    pub fn pow(&self, base: u64, mut exp: u64) -> u64 {
+        let mut base = self.reduce(base as u128);
+        let mut result = self.reduce(1);
+        while exp > 0 {
+            if exp & 1 != 0 {
+                result = self.mul_mod(result, base);
+            }
+            base = self.mul_mod(base, base);
+            exp >>= 1;
+        }
+        result
+    }
+}
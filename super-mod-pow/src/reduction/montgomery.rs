@@ -0,0 +1,92 @@
+use crate::ModPowError;
+
+/// 奇数の法に対するモンゴメリ乗算 (R = 2^64)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Montgomery {
+    n: u64,
+    /// n * n_inv ≡ 1 (mod 2^64)
+    n_inv: u64,
+    /// R^2 mod n
+    r2: u64,
+}
+
+impl Montgomery {
+    /// # Panics
+    /// `n`が偶数のとき。パニックさせたくなければ[`Montgomery::try_new`]を使う
+    pub fn new(n: u64) -> Self {
+        Self::try_new(n).expect("Montgomery modulus must be odd")
+    }
+
+    pub fn try_new(n: u64) -> Result<Self, ModPowError> {
+        if n & 1 == 0 {
+            return Err(ModPowError::EvenModulus(n));
+        }
+        // ニュートン法: 1反復ごとに正しいビット数が倍になる (n * n ≡ 1 mod 8 から開始)
+        let mut n_inv = n;
+        for _ in 0..5 {
+            n_inv = n_inv.wrapping_mul(2u64.wrapping_sub(n.wrapping_mul(n_inv)));
+        }
+        let r2 = ((n as u128).wrapping_neg() % n as u128) as u64;
+        Ok(Self { n, n_inv, r2 })
+    }
+
+    #[inline]
+    pub fn modulus(&self) -> u64 {
+        self.n
+    }
+
+    /// t * R^-1 mod n (t < n * 2^64)
+    #[inline]
+    fn reduce(&self, t: u128) -> u64 {
+        let m = (t as u64).wrapping_mul(self.n_inv);
+        let mn = ((m as u128 * self.n as u128) >> 64) as u64;
+        let (r, borrow) = ((t >> 64) as u64).overflowing_sub(mn);
+        if borrow {
+            r.wrapping_add(self.n)
+        } else {
+            r
+        }
+    }
+
+    /// `a * R mod n`。`a * R^2 < n * 2^64`なので、`a >= n`でも先に`n`で割らなくてよい
+    #[inline]
+    pub fn to_mont(&self, a: u64) -> u64 {
+        self.reduce(a as u128 * self.r2 as u128)
+    }
+
+    #[inline]
+    pub fn from_mont(&self, a: u64) -> u64 {
+        self.reduce(a as u128)
+    }
+
+    /// モンゴメリ表現同士の積
+    #[inline]
+    pub fn mul_mont(&self, a: u64, b: u64) -> u64 {
+        self.reduce(a as u128 * b as u128)
+    }
+
+    /// 通常の表現同士の積。`aR * b * R^-1 = ab`なので変換は片方だけでよい
+    ///
+    /// 同じ法で何度も掛けるときは[`to_mont`](Self::to_mont)で変換しておき、[`mul_mont`](Self::mul_mont)を使う。
+    #[inline]
+    pub fn mul_mod(&self, a: u64, b: u64) -> u64 {
+        self.reduce(self.to_mont(a) as u128 * b as u128)
+    }
+
+    /// モンゴメリ表現のまま`base^exp`を計算する
+    pub fn pow_mont(&self, mut base: u64, mut exp: u64) -> u64 {
+        let mut result = self.to_mont(1);
+        while exp > 0 {
+            if exp & 1 != 0 {
+                result = self.mul_mont(result, base);
+            }
+            base = self.mul_mont(base, base);
+            exp >>= 1;
+        }
+        result
+    }
+
+    pub fn pow(&self, base: u64, exp: u64) -> u64 {
+        self.from_mont(self.pow_mont(self.to_mont(base), exp))
+    }
+}
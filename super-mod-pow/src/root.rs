@@ -0,0 +1,157 @@
+//! 剰余環での冪根
+
+use crate::{crt, discrete_log, factorize, is_prime, mod_inverse, primitive_root, uint::gcd, Dynamic, ModInt};
+
+/// 素数`p`を法とする平方根 (Tonelli–Shanks)
+///
+/// 2つの根のうち小さい方を返す。平方剰余でないときや`p`が素数でないときは`None`
+pub fn sqrt_mod(a: u64, p: u64) -> Option<u64> {
+    if !is_prime(p) {
+        return None;
+    }
+    let modulus = Dynamic::new(p);
+    let a = ModInt::new(a, modulus);
+    if p == 2 || a.value() == 0 {
+        return Some(a.value());
+    }
+    let one = ModInt::one(modulus);
+    let minus_one = -one;
+    if a.pow((p - 1) / 2) != one {
+        return None;
+    }
+    let s = (p - 1).trailing_zeros();
+    let q = (p - 1) >> s;
+    let z = (2..)
+        .map(|z| ModInt::new(z, modulus))
+        .find(|z| z.pow((p - 1) / 2) == minus_one)
+        .unwrap();
+    let mut m = s;
+    let mut c = z.pow(q);
+    let mut t = a.pow(q);
+    let mut r = a.pow(q.div_ceil(2));
+    while t != one {
+        // t^(2^i) = 1 となる最小のi
+        let mut i = 0;
+        let mut t2 = t;
+        while t2 != one {
+            t2 *= t2;
+            i += 1;
+        }
+        let b = c.pow(1 << (m - i - 1));
+        m = i;
+        c = b * b;
+        t *= c;
+        r *= b;
+    }
+    Some(r.value().min(p - r.value()))
+}
+
+/// 単数`a`の`p^e`を法とする`k`乗根
+fn kth_root_unit(a: u64, k: u64, p: u64, e: u32) -> Option<u64> {
+    let n = p.pow(e);
+    let modulus = Dynamic::new(n);
+    if p == 2 && e >= 3 {
+        // (Z/2^eZ)^* = {±1} × <5> (5の位数は2^(e-2))
+        let order = 1u64 << (e - 2);
+        let (sign, rest) = if a % 4 == 1 { (1, a) } else { (n - 1, n - a) };
+        let t = discrete_log(5, rest, n)?;
+        let (g, inv, _) = crate::ext_gcd(k % order, order);
+        if !t.is_multiple_of(g) || (k.is_multiple_of(2) && sign != 1) {
+            return None;
+        }
+        let y = ((t / g) as u128 * inv.rem_euclid(order as i128) as u128 % (order / g) as u128) as u64;
+        let x = ModInt::new(5, modulus).pow(y) * ModInt::new(sign, modulus);
+        return Some(x.value());
+    }
+    // 原始根gを使って a = g^t, x = g^y と書くと k * y ≡ t (mod φ)
+    let g = primitive_root(n)?;
+    let phi = n / p * (p - 1);
+    let t = discrete_log(g, a, n)?;
+    let d = gcd(k, phi);
+    if !t.is_multiple_of(d) {
+        return None;
+    }
+    let y = ((t / d) as u128 * mod_inverse(k / d, phi / d)? as u128 % (phi / d) as u128) as u64;
+    Some(ModInt::new(g, modulus).pow(y).value())
+}
+
+/// 素数冪`p^e`を法とする`k`乗根
+fn kth_root_prime_power(a: u64, k: u64, p: u64, e: u32) -> Option<u64> {
+    let n = p.pow(e);
+    let a = a % n;
+    if a == 0 {
+        return Some(0);
+    }
+    // a = p^v * u と分けると、x = p^(v/k) * y (y^k ≡ u mod p^(e-v))
+    let mut v = 0;
+    let mut u = a;
+    while u.is_multiple_of(p) {
+        u /= p;
+        v += 1;
+    }
+    if !(v as u64).is_multiple_of(k) {
+        return None;
+    }
+    let y = kth_root_unit(u % p.pow(e - v), k, p, e - v)?;
+    let x = ModInt::new(p.pow((v as u64 / k) as u32), Dynamic::new(n)) * ModInt::new(y, Dynamic::new(n));
+    Some(x.value())
+}
+
+/// `x^k ≡ a (mod n)`となる`x`を1つ求める
+///
+/// `n`を素数冪に分解し、それぞれの根を中国剰余定理で合わせる。根が存在しないときは`None`。
+/// `0^0 = 1`とする。
+pub fn kth_root(a: u64, k: u64, n: u64) -> Option<u64> {
+    if n == 0 {
+        return None;
+    }
+    if k == 0 {
+        return (a % n == 1 % n).then_some(0);
+    }
+    let mut congruences = Vec::new();
+    for &(p, e) in &factorize(n) {
+        congruences.push((kth_root_prime_power(a, k, p, e)?, p.pow(e)));
+    }
+    crt(&congruences).ok().map(|(x, _)| x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpow;
+
+    #[test]
+    fn square_root() {
+        for p in (2..500).filter(|&p| is_prime(p)) {
+            for a in 0..p {
+                let expected = (0..p).find(|&x| x * x % p == a);
+                assert_eq!(sqrt_mod(a, p), expected, "sqrt({a}) mod {p}");
+            }
+        }
+        let p = u64::MAX - 58;
+        let r = sqrt_mod(mpow(123_456_789, 2, p), p).unwrap();
+        assert_eq!(r.min(p - r), 123_456_789);
+        assert_eq!(sqrt_mod(4, 15), None);
+    }
+
+    #[test]
+    fn kth_roots() {
+        for n in 1..=100 {
+            for k in 0..=6 {
+                for a in 0..n {
+                    let exists = (0..n).any(|x| mpow(x, k, n) == a);
+                    match kth_root(a, k, n) {
+                        Some(x) => assert_eq!(mpow(x, k, n), a, "{x}^{k} mod {n}"),
+                        None => assert!(!exists, "{a}^(1/{k}) mod {n}"),
+                    }
+                }
+            }
+        }
+        let p = 998_244_353;
+        let x = kth_root(mpow(12345, 7 * 17, p), 7 * 17, p).unwrap();
+        assert_eq!(mpow(x, 7 * 17, p), mpow(12345, 7 * 17, p));
+        assert_eq!(kth_root(16, 1 << 32, 16), Some(0));
+        assert_eq!(kth_root(8, 1 << 32, 16), None);
+        assert_eq!(kth_root(1, 1 << 32, 16).map(|x| mpow(x, 1 << 32, 16)), Some(1));
+    }
+}
@@ -0,0 +1,363 @@
+//! 素数篩
+
+use crate::{Factorization, ModPowError};
+
+/// 要素数`words`の表を確保する。`len`は篩う整数の個数で、エラーに載せる
+///
+/// `vec!`は確保に失敗するとアボートするので`try_reserve_exact`を使う。
+fn try_alloc<T: Clone>(len: u128, words: u128, fill: T) -> Result<Vec<T>, ModPowError> {
+    let too_large = ModPowError::SieveTooLarge { len };
+    let words = usize::try_from(words).map_err(|_| too_large)?;
+    let mut table = Vec::new();
+    table.try_reserve_exact(words).map_err(|_| too_large)?;
+    table.resize(words, fill);
+    Ok(table)
+}
+
+/// 奇数だけを1bitずつ持つエラトステネスの篩
+///
+/// `i`番目のbitが`2 * i + 1`に対応する。
+#[derive(Clone, Debug)]
+pub struct Sieve {
+    limit: u64,
+    bits: Vec<u64>,
+}
+
+impl Sieve {
+    /// `0..=limit`の素数を列挙する
+    ///
+    /// # Panics
+    /// 表を確保できないとき。パニックさせたくなければ[`Sieve::try_new`]を使う
+    pub fn new(limit: u64) -> Self {
+        Self::try_new(limit).expect("sieve is too large")
+    }
+
+    pub fn try_new(limit: u64) -> Result<Self, ModPowError> {
+        let len = limit / 2 + (limit & 1);
+        let mut bits = try_alloc(limit as u128 + 1, len.div_ceil(64) as u128, !0u64)?;
+        let len = len as usize;
+        // 1は素数でない
+        if let Some(first) = bits.first_mut() {
+            *first &= !1;
+        }
+        if !len.is_multiple_of(64) {
+            *bits.last_mut().unwrap() &= (1 << (len % 64)) - 1;
+        }
+        let mut p = 3;
+        while p <= limit / p {
+            if bits[(p / 2 / 64) as usize] >> (p / 2 % 64) & 1 != 0 {
+                let mut i = p * p / 2;
+                while i < len as u64 {
+                    bits[(i / 64) as usize] &= !(1 << (i % 64));
+                    i += p;
+                }
+            }
+            p += 2;
+        }
+        Ok(Self { limit, bits })
+    }
+
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    /// `n > self.limit()`のときは[`is_prime`](crate::is_prime)で判定する
+    pub fn is_prime(&self, n: u64) -> bool {
+        if n > self.limit {
+            return crate::is_prime(n);
+        }
+        if n.is_multiple_of(2) {
+            return n == 2;
+        }
+        let i = n / 2;
+        self.bits[(i / 64) as usize] >> (i % 64) & 1 != 0
+    }
+
+    pub fn primes(&self) -> Primes<'_> {
+        Primes {
+            two: self.limit >= 2,
+            bits: BitIter::new(&self.bits),
+        }
+    }
+
+    /// `n`以下の素数の個数。`n > self.limit()`のときは`None`
+    pub fn prime_pi(&self, n: u64) -> Option<usize> {
+        (n <= self.limit).then(|| self.count_upto(n))
+    }
+
+    /// `self.limit()`以下の素数の個数
+    pub fn count(&self) -> usize {
+        self.count_upto(self.limit)
+    }
+
+    fn count_upto(&self, n: u64) -> usize {
+        let len = n.div_ceil(2);
+        let (words, rest) = ((len / 64) as usize, len % 64);
+        let mut count = self.bits[..words].iter().map(|w| w.count_ones() as usize).sum::<usize>();
+        if rest != 0 {
+            count += (self.bits[words] & ((1 << rest) - 1)).count_ones() as usize;
+        }
+        count + (n >= 2) as usize
+    }
+}
+
+/// 立っているbitの位置を昇順に返す
+#[derive(Clone, Debug)]
+struct BitIter<'a> {
+    words: &'a [u64],
+    offset: u64,
+    current: u64,
+}
+
+impl<'a> BitIter<'a> {
+    fn new(words: &'a [u64]) -> Self {
+        match words.split_first() {
+            Some((&current, words)) => Self { words, offset: 0, current },
+            None => Self { words, offset: 0, current: 0 },
+        }
+    }
+}
+
+impl Iterator for BitIter<'_> {
+    type Item = u64;
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.current == 0 {
+            let (&next, rest) = self.words.split_first()?;
+            self.words = rest;
+            self.offset += 64;
+            self.current = next;
+        }
+        let bit = self.current.trailing_zeros() as u64;
+        self.current &= self.current - 1;
+        Some(self.offset + bit)
+    }
+}
+
+/// [`Sieve::primes`]が返すイテレータ
+#[derive(Clone, Debug)]
+pub struct Primes<'a> {
+    two: bool,
+    bits: BitIter<'a>,
+}
+
+impl Iterator for Primes<'_> {
+    type Item = u64;
+    fn next(&mut self) -> Option<Self::Item> {
+        if std::mem::take(&mut self.two) {
+            return Some(2);
+        }
+        self.bits.next().map(|i| 2 * i + 1)
+    }
+}
+
+/// `lo..=hi`の区間篩
+///
+/// `sqrt(hi)`以下の素数だけを使うので、`[10^12, 10^12 + 10^6]`のような区間も`O(sqrt(hi) + (hi - lo))`の
+/// メモリで計算できる。
+#[derive(Clone, Debug)]
+pub struct SegmentedSieve {
+    lo: u64,
+    hi: u64,
+    bits: Vec<u64>,
+}
+
+impl SegmentedSieve {
+    /// `lo..=hi`の素数を列挙する。`lo > hi`のときは空
+    ///
+    /// # Panics
+    /// 表を確保できないとき。パニックさせたくなければ[`SegmentedSieve::try_new`]を使う
+    pub fn new(lo: u64, hi: u64) -> Self {
+        Self::try_new(lo, hi).expect("sieve is too large")
+    }
+
+    pub fn try_new(lo: u64, hi: u64) -> Result<Self, ModPowError> {
+        // (0, u64::MAX) では個数が2^64になるのでu128で数える
+        let len = if lo <= hi { (hi - lo) as u128 + 1 } else { 0 };
+        let mut bits = try_alloc(len, len.div_ceil(64), !0u64)?;
+        if !len.is_multiple_of(64) {
+            *bits.last_mut().unwrap() &= (1 << (len % 64)) - 1;
+        }
+        let mut clear = |n: u64| {
+            let i = n - lo;
+            bits[(i / 64) as usize] &= !(1 << (i % 64));
+        };
+        for n in lo..=hi.min(1) {
+            clear(n);
+        }
+        for p in Sieve::try_new(hi.isqrt())?.primes() {
+            let Some(first) = lo.div_ceil(p).checked_mul(p) else {
+                continue;
+            };
+            let mut n = (p * p).max(first);
+            while n <= hi {
+                clear(n);
+                match n.checked_add(p) {
+                    Some(next) => n = next,
+                    None => break,
+                }
+            }
+        }
+        Ok(Self { lo, hi, bits })
+    }
+
+    pub fn range(&self) -> std::ops::RangeInclusive<u64> {
+        self.lo..=self.hi
+    }
+
+    /// `n`が区間外のときは[`is_prime`](crate::is_prime)で判定する
+    pub fn is_prime(&self, n: u64) -> bool {
+        if !self.range().contains(&n) {
+            return crate::is_prime(n);
+        }
+        let i = n - self.lo;
+        self.bits[(i / 64) as usize] >> (i % 64) & 1 != 0
+    }
+
+    pub fn primes(&self) -> impl Iterator<Item = u64> + '_ {
+        BitIter::new(&self.bits).map(|i| self.lo + i)
+    }
+
+    /// 区間内の素数の個数
+    pub fn count(&self) -> usize {
+        self.bits.iter().map(|w| w.count_ones() as usize).sum()
+    }
+}
+
+/// 線形篩による最小素因数テーブル
+///
+/// 構築後は`limit`以下の整数を`O(log n)`で素因数分解できる。
+#[derive(Clone, Debug)]
+pub struct LinearSieve {
+    spf: Vec<u32>,
+    primes: Vec<u32>,
+}
+
+impl LinearSieve {
+    /// # Panics
+    /// 表を確保できないとき。パニックさせたくなければ[`LinearSieve::try_new`]を使う
+    pub fn new(limit: u32) -> Self {
+        Self::try_new(limit).expect("sieve is too large")
+    }
+
+    pub fn try_new(limit: u32) -> Result<Self, ModPowError> {
+        let len = limit as usize + 1;
+        let mut spf = try_alloc(len as u128, len as u128, 0u32)?;
+        let mut primes = Vec::new();
+        for i in 2..len {
+            if spf[i] == 0 {
+                spf[i] = i as u32;
+                primes.push(i as u32);
+            }
+            for &p in &primes {
+                let j = i * p as usize;
+                if p > spf[i] || j >= len {
+                    break;
+                }
+                spf[j] = p;
+            }
+        }
+        Ok(Self { spf, primes })
+    }
+
+    pub fn limit(&self) -> u32 {
+        (self.spf.len() - 1) as u32
+    }
+
+    /// 最小素因数。`n < 2`のときは`None`
+    ///
+    /// `n > self.limit()`のときは[`factorize`](crate::factorize)で求める
+    pub fn smallest_prime_factor(&self, n: u32) -> Option<u32> {
+        match self.spf.get(n as usize) {
+            Some(&p) => Some(p).filter(|&p| p != 0),
+            None => crate::factorize(n as u64).primes().next().map(|p| p as u32),
+        }
+    }
+
+    pub fn is_prime(&self, n: u32) -> bool {
+        self.smallest_prime_factor(n) == Some(n)
+    }
+
+    pub fn primes(&self) -> &[u32] {
+        &self.primes
+    }
+
+    /// 最小素因数の表を使って素因数分解する
+    ///
+    /// `n > self.limit()`のときは[`factorize`](crate::factorize)で求める
+    pub fn factorize(&self, mut n: u32) -> Factorization {
+        if n > self.limit() {
+            return crate::factorize(n as u64);
+        }
+        let mut factorization = Factorization::default();
+        while let Some(p) = self.smallest_prime_factor(n) {
+            let mut count = 0;
+            while n.is_multiple_of(p) {
+                n /= p;
+                count += 1;
+            }
+            factorization.extend(std::iter::once((p as u64, count)));
+        }
+        factorization
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{factorize, is_prime};
+
+    #[test]
+    fn sieve() {
+        for limit in [0, 1, 2, 3, 63, 64, 127, 128, 129, 10_000] {
+            let sieve = Sieve::new(limit);
+            let expected = (0..=limit).filter(|&n| is_prime(n)).collect::<Vec<_>>();
+            assert_eq!(sieve.primes().collect::<Vec<_>>(), expected);
+            assert_eq!(sieve.count(), expected.len());
+            for n in 0..=limit {
+                assert_eq!(sieve.is_prime(n), is_prime(n));
+                assert_eq!(sieve.prime_pi(n), Some(expected.iter().take_while(|&&p| p <= n).count()));
+            }
+            assert_eq!(sieve.prime_pi(limit + 1), None);
+            assert!(sieve.is_prime(1_000_000_007));
+        }
+    }
+
+    #[test]
+    fn segmented() {
+        let ranges = [
+            (0, 0),
+            (0, 1000),
+            (999, 1001),
+            (1_000_000_000_000, 1_000_000_010_000),
+            (999_999_999_999_000, 1_000_000_000_001_000),
+            (10, 9),
+        ];
+        for (lo, hi) in ranges {
+            let sieve = SegmentedSieve::new(lo, hi);
+            let expected = (lo..=hi).filter(|&n| is_prime(n)).collect::<Vec<_>>();
+            assert_eq!(sieve.primes().collect::<Vec<_>>(), expected);
+            assert_eq!(sieve.count(), expected.len());
+            assert!(sieve.is_prime(1_000_000_007));
+        }
+        // 個数が2^64で、u64で数えると桁あふれしていた
+        assert_eq!(SegmentedSieve::try_new(0, u64::MAX).err(), Some(ModPowError::SieveTooLarge { len: 1 << 64 }));
+    }
+
+    #[test]
+    fn too_large() {
+        assert_eq!(Sieve::try_new(u64::MAX).err(), Some(ModPowError::SieveTooLarge { len: 1 << 64 }));
+        assert_eq!(Sieve::try_new(1 << 62).err(), Some(ModPowError::SieveTooLarge { len: (1 << 62) + 1 }));
+    }
+
+    #[test]
+    fn linear() {
+        let sieve = LinearSieve::new(10_000);
+        let primes = sieve.primes().iter().map(|&p| p as u64).collect::<Vec<_>>();
+        assert_eq!(primes, Sieve::new(10_000).primes().collect::<Vec<_>>());
+        for n in 0..=10_000 {
+            assert_eq!(sieve.factorize(n), factorize(n as u64), "{n}");
+        }
+        assert_eq!(sieve.factorize(1_000_006), factorize(1_000_006));
+        assert_eq!(sieve.smallest_prime_factor(1_000_003 * 7), Some(7));
+    }
+}
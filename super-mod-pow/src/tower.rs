@@ -0,0 +1,174 @@
+use crate::{factor::factorize, mpow, Dynamic, ModInt, ModPowError, UInt};
+
+/// 指数がこれ以上なら、`n`の素因数の指数(高々63)以上であることが保証される
+pub(crate) const CAP: u64 = 64;
+
+/// `min(b^c, cap)`
+pub(crate) fn capped_pow<T: UInt>(b: &T, c: &T, cap: u64) -> u64 {
+    if c.is_zero() {
+        return 1;
+    }
+    if *b <= T::one() {
+        return b.to_u64().unwrap();
+    }
+    // b >= 2 なので高々log2(cap)回でcapに達する
+    let b = b.to_u64().unwrap_or(u64::MAX);
+    let mut result = 1u64;
+    let mut e = c.clone();
+    while !e.is_zero() && result < cap {
+        result = result.saturating_mul(b);
+        e = e - T::one();
+    }
+    result.min(cap)
+}
+
+/// `tower mod n`
+///
+/// 法は1段ごとにλで小さくなり、高々128段ほどで1になる。そこから上は`mod 1`なので、
+/// 指数が[`CAP`]以上かどうかだけ分かればよい。
+fn eval(tower: &[u64], n: u64) -> u64 {
+    // 各段の(法, λ(法), 素因数の指数の最大値)
+    let mut levels = Vec::new();
+    let mut m = n;
+    while levels.len() < tower.len() && m > 1 {
+        let factorization = factorize(m);
+        let lambda = factorization.carmichael_lambda();
+        let max_k = factorization.iter().map(|&(_, k)| k as u64).max().unwrap_or(0);
+        levels.push((m, lambda, max_k));
+        m = lambda;
+    }
+    // 法が1になった段から上の値。`0^0 = 1`のように1段ずつしか決まらないので上から順に求める
+    let capped = tower[levels.len()..].iter().rev().fold(1, |e, a| capped_pow(a, &e, CAP));
+    let (mut e_mod, mut e_capped) = (capped % m, capped);
+    for (&a, &(m, lambda, max_k)) in tower.iter().zip(&levels).rev() {
+        let value = if e_capped < CAP {
+            // 指数が小さいときは正確な値で計算する
+            mpow(a, e_capped, m)
+        } else {
+            // e >= max_k のとき、a^e ≡ a^e' (mod m) (e' ≡ e (mod λ(m)), e' >= max_k)
+            let lambda = Dynamic::new(lambda);
+            let exp = ModInt::new(e_mod, lambda) - ModInt::new(max_k, lambda);
+            // max_k + exp はu64に収まらないことがあるので分けて掛ける
+            let a = ModInt::new(a, Dynamic::new(m));
+            (a.pow(max_k) * a.pow(exp.value())).value()
+        };
+        (e_mod, e_capped) = (value, capped_pow(&a, &e_capped, CAP));
+    }
+    e_mod
+}
+
+/// `tower[0]^(tower[1]^(tower[2]^...)) mod n`
+///
+/// 空の塔は1とみなし、`0^0 = 1`とする。`n = 0`のときは0
+pub fn tower_mod(tower: &[u64], n: u64) -> u64 {
+    try_tower_mod(tower, n).unwrap_or(0)
+}
+
+/// `n == 0`を[`ModPowError::ZeroModulus`]として区別する[`tower_mod`]
+pub fn try_tower_mod(tower: &[u64], n: u64) -> Result<u64, ModPowError> {
+    if n == 0 {
+        return Err(ModPowError::ZeroModulus);
+    }
+    Ok(eval(tower, n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 塔の正確な値。`u128`に収まらなければ`None`
+    fn exact(tower: &[u64]) -> Option<u128> {
+        let Some((&a, rest)) = tower.split_first() else {
+            return Some(1);
+        };
+        match (a, exact(rest)) {
+            (0 | 1, Some(0)) => Some(1),
+            (0 | 1, _) => Some(a as u128),
+            (_, e) => (a as u128).checked_pow(e?.try_into().ok()?),
+        }
+    }
+
+    /// 指数だけ正確に求めて、愚直に繰り返し二乗法で計算する
+    fn brute_force(tower: &[u64], n: u64) -> Option<u64> {
+        let Some((&a, rest)) = tower.split_first() else {
+            return Some(1 % n);
+        };
+        let mut e = exact(rest)?;
+        let (mut base, mut result) = (a as u128 % n as u128, 1 % n as u128);
+        while e > 0 {
+            if e & 1 != 0 {
+                result = result * base % n as u128;
+            }
+            base = base * base % n as u128;
+            e >>= 1;
+        }
+        Some(result as u64)
+    }
+
+    #[test]
+    fn small_towers() {
+        let mut towers = vec![vec![]];
+        for _ in 0..4 {
+            let next = towers
+                .iter()
+                .filter(|t| t.len() == towers.last().unwrap().len())
+                .flat_map(|t| (0..6).map(move |a| [t.as_slice(), &[a]].concat()))
+                .collect::<Vec<_>>();
+            towers.extend(next);
+        }
+        let mut checked = 0;
+        for tower in &towers {
+            for n in 1..=200 {
+                if let Some(expected) = brute_force(tower, n) {
+                    assert_eq!(tower_mod(tower, n), expected, "{tower:?} mod {n}");
+                    checked += 1;
+                }
+            }
+        }
+        assert!(checked > 100_000);
+    }
+
+    #[test]
+    fn threshold() {
+        // 2^(2^2) = 16 < 32 なので正確な指数が必要
+        assert_eq!(tower_mod(&[2, 2, 2], 32), 16);
+        assert_eq!(tower_mod(&[2, 2, 2, 2], 1 << 20), 65536);
+        assert_eq!(tower_mod(&[2, 2, 2, 2, 2], 1 << 20), 0);
+        assert_eq!(tower_mod(&[2, 3, 2], 1 << 10), 512);
+        assert_eq!(tower_mod(&[6, 1, 5], 1 << 62), 6);
+        assert_eq!(tower_mod(&[10, 1, 0, 0], 1_000), 10);
+        assert_eq!(tower_mod(&[], 7), 1);
+        assert_eq!(tower_mod(&[3, 0], 1), 0);
+    }
+
+    #[test]
+    fn large_lambda() {
+        // λ(p) = p - 1 > 2^63 で、指数を足し合わせるとu64をあふれていた
+        let p = 18446744073709551557;
+        assert_eq!(tower_mod(&[3, 2, 100], p), 8788177927020910494);
+        assert_eq!(tower_mod(&[p - 1, 2, 100], p), 1);
+    }
+
+    #[test]
+    fn tall_tower() {
+        // 段数だけ再帰すると、スタックがあふれていた
+        let mut tower = vec![2; 1_000_000];
+        assert_eq!(tower_mod(&tower, 1000), tower_mod(&tower[..100], 1000));
+        // 途中の0や1で上の段が打ち消される
+        tower[999_998] = 0;
+        tower[999_999] = 0;
+        assert_eq!(tower_mod(&tower, 1 << 40), tower_mod(&tower[..999_998], 1 << 40));
+        tower[1] = 1;
+        assert_eq!(tower_mod(&tower, 1000), 2);
+    }
+
+    #[test]
+    fn try_tall_tower() {
+        // パニックしない版も段数で再帰しない
+        let tower = vec![u64::MAX; 1_000_000];
+        for n in [1, 2, 1000, 1 << 63, u64::MAX] {
+            assert_eq!(try_tower_mod(&tower, n), Ok(tower_mod(&tower[..200], n)), "mod {n}");
+        }
+        assert_eq!(try_tower_mod(&tower, 0), Err(ModPowError::ZeroModulus));
+    }
+}
@@ -0,0 +1,551 @@
+//! 符号なし整数型を抽象化するトレイト
+//!
+//! `u32`, `u64`, `u128`に実装している。`bigint`フィーチャーを有効にすると`num_bigint::BigUint`にも実装される。
+
+use std::fmt;
+
+use num_traits::{CheckedMul, Num, ToPrimitive, Unsigned};
+
+use crate::{factor, Dynamic, ModInt};
+
+/// ミラー・ラビン判定法の底
+const BASES: [u32; 13] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41];
+
+/// [`BASES`]の全てに対する最小の強擬素数。これ未満ではミラー・ラビン判定法が決定的になる (Sorenson–Webster)
+const MR_DETERMINISTIC_BOUND: u128 = 3_317_044_064_679_887_385_961_981;
+
+/// 剰余演算と素因数分解ができる符号なし整数
+pub trait UInt: Clone + Ord + fmt::Debug + fmt::Display + From<u32> + Num + Unsigned + CheckedMul + ToPrimitive {
+    /// `self * rhs mod modulus`。`modulus == 0`のときは0
+    fn mul_mod(&self, rhs: &Self, modulus: &Self) -> Self;
+
+    fn is_even(&self) -> bool {
+        (self.clone() % Self::from(2u32)).is_zero()
+    }
+
+    /// `self^exp mod modulus`。`modulus == 0`のときは0
+    fn pow_mod(&self, exp: &Self, modulus: &Self) -> Self {
+        if modulus.is_zero() {
+            return Self::zero();
+        }
+        let two = Self::from(2u32);
+        let mut base = self.clone() % modulus.clone();
+        let mut exp = exp.clone();
+        let mut result = Self::one() % modulus.clone();
+        while !exp.is_zero() {
+            if !exp.is_even() {
+                result = result.mul_mod(&base, modulus);
+            }
+            base = base.mul_mod(&base, modulus);
+            exp = exp / two.clone();
+        }
+        result
+    }
+
+    /// 素数判定
+    ///
+    /// `3.3 * 10^24`未満では最初の13個の素数を底とするミラー・ラビン判定法で決定的に判定する。
+    /// それ以上では底2のミラー・ラビン判定法と強いリュカ判定法を組み合わせたBPSW判定法を使う。
+    /// BPSW判定法は`2^64`未満で誤りがないことが確かめられており、それ以上でも反例は知られていない。
+    fn is_prime(&self) -> bool {
+        is_prime_generic(self)
+    }
+
+    /// 素因数を重複込みで`primes`に追加する。順序は問わない
+    fn factor_into(&self, primes: &mut Vec<Self>) {
+        pollard_rho(self, primes)
+    }
+}
+
+fn is_prime_generic<T: UInt>(n: &T) -> bool {
+    for p in BASES {
+        let p = T::from(p);
+        if *n == p {
+            return true;
+        }
+        if (n.clone() % p).is_zero() {
+            return false;
+        }
+    }
+    if *n < T::from(2u32) {
+        return false;
+    }
+    match n.to_u128() {
+        Some(m) if m < MR_DETERMINISTIC_BOUND => BASES.iter().all(|&a| strong_probable_prime(n, &T::from(a))),
+        _ => strong_probable_prime(n, &T::from(2u32)) && strong_lucas_probable_prime(n),
+    }
+}
+
+/// 奇数`n`が底`a`の強確率的素数か
+fn strong_probable_prime<T: UInt>(n: &T, a: &T) -> bool {
+    let one = T::one();
+    let minus_one = n.clone() - one.clone();
+    let mut d = minus_one.clone();
+    let mut s = 0;
+    while d.is_even() {
+        d = d / T::from(2u32);
+        s += 1;
+    }
+    let mut x = a.pow_mod(&d, n);
+    if x == one || x == minus_one {
+        return true;
+    }
+    for _ in 1..s {
+        x = x.mul_mod(&x, n);
+        if x == minus_one {
+            return true;
+        }
+    }
+    false
+}
+
+fn add_mod<T: UInt>(a: T, b: T, n: &T) -> T {
+    let rest = n.clone() - b.clone();
+    if a >= rest {
+        a - rest
+    } else {
+        a + b
+    }
+}
+
+fn sub_mod<T: UInt>(a: T, b: T, n: &T) -> T {
+    if a >= b {
+        a - b
+    } else {
+        n.clone() - (b - a)
+    }
+}
+
+/// 奇数`n`を法とする`x / 2`
+fn half_mod<T: UInt>(x: T, n: &T) -> T {
+    let two = T::from(2u32);
+    if x.is_even() {
+        x / two
+    } else {
+        // (x + n) / 2 を桁あふれせずに求める
+        x / two.clone() + n.clone() / two + T::one()
+    }
+}
+
+/// ヤコビ記号`(a / n)` (`n`は正の奇数)
+fn jacobi<T: UInt>(a: &T, n: &T) -> i32 {
+    let (mut a, mut n) = (a.clone() % n.clone(), n.clone());
+    let (three, four, five, eight) = (T::from(3u32), T::from(4u32), T::from(5u32), T::from(8u32));
+    let mut sign = 1;
+    while !a.is_zero() {
+        while a.is_even() {
+            a = a / T::from(2u32);
+            let r = n.clone() % eight.clone();
+            if r == three || r == five {
+                sign = -sign;
+            }
+        }
+        std::mem::swap(&mut a, &mut n);
+        if a.clone() % four.clone() == three && n.clone() % four.clone() == three {
+            sign = -sign;
+        }
+        a = a % n.clone();
+    }
+    if n.is_one() {
+        sign
+    } else {
+        0
+    }
+}
+
+fn is_square<T: UInt>(n: &T) -> bool {
+    // ニュートン法による整数平方根
+    let two = T::from(2u32);
+    let mut x = n.clone();
+    let mut y = x.clone() / two.clone() + T::one();
+    while y < x {
+        x = y;
+        y = (x.clone() + n.clone() / x.clone()) / two.clone();
+    }
+    x.clone() * x == *n
+}
+
+/// 奇数`n`が強いリュカ確率的素数か (Selfridgeの方法でパラメータを選ぶ)
+fn strong_lucas_probable_prime<T: UInt>(n: &T) -> bool {
+    if is_square(n) {
+        return false;
+    }
+    // D = 5, -7, 9, -11, ... のうち (D / n) = -1 となる最初のもの
+    let residue = |d: u32, negative: bool| {
+        let d = T::from(d) % n.clone();
+        if negative && !d.is_zero() {
+            n.clone() - d
+        } else {
+            d
+        }
+    };
+    let (mut d, mut negative) = (5u32, false);
+    loop {
+        match jacobi(&residue(d, negative), n) {
+            -1 => break,
+            // nがDと共通の素因数を持つ
+            0 if T::from(d) != *n => return false,
+            _ => {}
+        }
+        d += 2;
+        negative = !negative;
+    }
+    let disc = residue(d, negative);
+    // P = 1, Q = (1 - D) / 4
+    let q = half_mod(half_mod(sub_mod(T::one(), disc.clone(), n), n), n);
+    // n + 1 = k * 2^s (kは奇数)。n + 1 は桁あふれしうるので作らない
+    let mut k = n.clone() / T::from(2u32) + T::one();
+    let mut s = 1;
+    while k.is_even() {
+        k = k / T::from(2u32);
+        s += 1;
+    }
+    let mut bits = Vec::new();
+    while !k.is_zero() {
+        bits.push(!k.is_even());
+        k = k / T::from(2u32);
+    }
+    let double = |v: &T, qk: &T| sub_mod(v.mul_mod(v, n), add_mod(qk.clone(), qk.clone(), n), n);
+    // U_1 = 1, V_1 = P, Q^1 から添字の2倍と1加算で U_k, V_k, Q^k を求める
+    let (mut u, mut v, mut qk) = (T::one(), T::one(), q.clone());
+    for &bit in bits.iter().rev().skip(1) {
+        u = u.mul_mod(&v, n);
+        v = double(&v, &qk);
+        qk = qk.mul_mod(&qk, n);
+        if bit {
+            let (u1, v1) = (u, v);
+            u = half_mod(add_mod(u1.clone(), v1.clone(), n), n);
+            v = half_mod(add_mod(disc.mul_mod(&u1, n), v1, n), n);
+            qk = qk.mul_mod(&q, n);
+        }
+    }
+    if u.is_zero() || v.is_zero() {
+        return true;
+    }
+    for _ in 1..s {
+        v = double(&v, &qk);
+        qk = qk.mul_mod(&qk, n);
+        if v.is_zero() {
+            return true;
+        }
+    }
+    false
+}
+
+fn pollard_rho<T: UInt>(n: &T, primes: &mut Vec<T>) {
+    let mut n = n.clone();
+    if n.is_zero() {
+        return;
+    }
+    for p in BASES {
+        let p = T::from(p);
+        while (n.clone() % p.clone()).is_zero() {
+            n = n / p.clone();
+            primes.push(p.clone());
+        }
+    }
+    rho_factor_into(n, primes);
+}
+
+/// `a`の`m`を法とする逆元。存在しなければ`None`
+pub(crate) fn inverse<T: UInt>(a: &T, m: &T) -> Option<T> {
+    // s * a ≡ r (mod m) を保ちながら互除法を進める
+    let (mut r0, mut r1) = (a.clone() % m.clone(), m.clone());
+    let (mut s0, mut s1) = (T::one() % m.clone(), T::zero());
+    while !r1.is_zero() {
+        let q = r0.clone() / r1.clone();
+        let r = r0 - q.clone() * r1.clone();
+        let qs = q.mul_mod(&s1, m);
+        let s = sub_mod(s0, qs, m);
+        (r0, r1) = (r1, r);
+        (s0, s1) = (s1, s);
+    }
+    r0.is_one().then_some(s0)
+}
+
+/// `n`の素因数とその指数を素因数の昇順で`map`に追加する
+///
+/// `n`が0または1のときは何も追加しない。
+pub fn prime_factors<T: UInt, M: Extend<(T, u32)>>(n: T, map: &mut M) {
+    let mut primes = Vec::new();
+    n.factor_into(&mut primes);
+    primes.sort_unstable();
+    let mut factors: Vec<(T, u32)> = Vec::new();
+    for p in primes {
+        match factors.last_mut() {
+            Some((q, k)) if *q == p => *k += 1,
+            _ => factors.push((p, 1)),
+        }
+    }
+    map.extend(factors);
+}
+
+pub(crate) fn gcd<T: UInt>(mut a: T, mut b: T) -> T {
+    while !b.is_zero() {
+        let r = a % b.clone();
+        a = b;
+        b = r;
+    }
+    a
+}
+
+/// 小さい素因数を取り除いた`n`をPollard's rhoで分解する
+fn rho_factor_into<T: UInt>(n: T, primes: &mut Vec<T>) {
+    if n.is_one() {
+        return;
+    }
+    if n.is_prime() {
+        primes.push(n);
+        return;
+    }
+    let d = find_factor(&n);
+    rho_factor_into(n.clone() / d.clone(), primes);
+    rho_factor_into(d, primes);
+}
+
+/// 奇数の合成数`n`の非自明な約数を1つ求める (Pollard's rho, Brent's variant)
+fn find_factor<T: UInt>(n: &T) -> T {
+    // gcdをまとめて取る間隔
+    const M: u64 = 128;
+    let abs_diff = |a: &T, b: &T| if a > b { a.clone() - b.clone() } else { b.clone() - a.clone() };
+    let mut c = T::one();
+    loop {
+        let f = |x: &T| add_mod(x.mul_mod(x, n), c.clone(), n);
+        let mut y = T::from(2u32);
+        let mut x = y.clone();
+        let mut ys = y.clone();
+        let mut q = T::one();
+        let mut g = T::one();
+        let mut r = 1;
+        while g.is_one() {
+            x = y.clone();
+            for _ in 0..r {
+                y = f(&y);
+            }
+            let mut k = 0;
+            while k < r && g.is_one() {
+                ys = y.clone();
+                for _ in 0..M.min(r - k) {
+                    y = f(&y);
+                    q = q.mul_mod(&abs_diff(&x, &y), n);
+                }
+                g = gcd(q.clone(), n.clone());
+                k += M;
+            }
+            r <<= 1;
+        }
+        if g == *n {
+            // まとめすぎて全ての因数を同時に拾ったので1歩ずつやり直す
+            loop {
+                ys = f(&ys);
+                g = gcd(abs_diff(&x, &ys), n.clone());
+                if !g.is_one() {
+                    break;
+                }
+            }
+        }
+        if g != *n {
+            return g;
+        }
+        c = c + T::one();
+    }
+}
+
+/// `a * b`を`(上位128bit, 下位128bit)`で返す
+fn widening_mul(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = u64::MAX as u128;
+    let (a1, a0) = (a >> 64, a & MASK);
+    let (b1, b0) = (b >> 64, b & MASK);
+    let (ll, lh, hl, hh) = (a0 * b0, a0 * b1, a1 * b0, a1 * b1);
+    let mid = (ll >> 64) + (lh & MASK) + (hl & MASK);
+    (hh + (lh >> 64) + (hl >> 64) + (mid >> 64), (ll & MASK) | (mid << 64))
+}
+
+/// `(hi * 2^64 + lo) mod d`。`d`は最上位bitが立っていて、`hi < d`
+///
+/// 上位2桁を`d`の上位1桁で割った商の推定値は、真の商より高々2大きい (Knuth, Algorithm D)。
+fn rem_192_by_128(hi: u128, lo: u64, d: u128) -> u128 {
+    let d1 = (d >> 64) as u64;
+    let q = (hi / d1 as u128).min(u64::MAX as u128);
+    // p = q * d を192bitで持つ
+    let p_lo = q * (d as u64) as u128;
+    let mut p_hi = q * d1 as u128 + (p_lo >> 64);
+    let mut p_lo = p_lo as u64;
+    while (p_hi, p_lo) > (hi, lo) {
+        let (l, borrow) = p_lo.overflowing_sub(d as u64);
+        p_hi = p_hi - d1 as u128 - borrow as u128;
+        p_lo = l;
+    }
+    let (r_lo, borrow) = lo.overflowing_sub(p_lo);
+    let r_hi = hi - p_hi - borrow as u128;
+    (r_hi << 64) | r_lo as u128
+}
+
+impl UInt for u32 {
+    fn mul_mod(&self, rhs: &Self, modulus: &Self) -> Self {
+        (*self as u64 * *rhs as u64).checked_rem(*modulus as u64).unwrap_or(0) as u32
+    }
+
+    fn pow_mod(&self, exp: &Self, modulus: &Self) -> Self {
+        (*self as u64).pow_mod(&(*exp as u64), &(*modulus as u64)) as u32
+    }
+
+    fn is_prime(&self) -> bool {
+        factor::is_prime(*self as u64)
+    }
+
+    fn factor_into(&self, primes: &mut Vec<Self>) {
+        let mut factors = Vec::new();
+        (*self as u64).factor_into(&mut factors);
+        primes.extend(factors.into_iter().map(|p| p as u32));
+    }
+}
+
+impl UInt for u64 {
+    fn mul_mod(&self, rhs: &Self, modulus: &Self) -> Self {
+        (*self as u128 * *rhs as u128).checked_rem(*modulus as u128).unwrap_or(0) as u64
+    }
+
+    fn pow_mod(&self, exp: &Self, modulus: &Self) -> Self {
+        Dynamic::try_new(*modulus).map_or(0, |m| ModInt::new(*self, m).pow(*exp).value())
+    }
+
+    fn is_prime(&self) -> bool {
+        factor::is_prime(*self)
+    }
+
+    fn factor_into(&self, primes: &mut Vec<Self>) {
+        factor::factor_into(*self, primes);
+    }
+}
+
+impl UInt for u128 {
+    fn mul_mod(&self, rhs: &Self, modulus: &Self) -> Self {
+        let m = *modulus;
+        if m == 0 {
+            return 0;
+        }
+        let (a, b) = (*self % m, *rhs % m);
+        if let Some(product) = a.checked_mul(b) {
+            return product % m;
+        }
+        // 桁あふれするのは m > 2^64 のときだけなので、正規化後の上位桁は2^63以上になる
+        let (hi, lo) = widening_mul(a, b);
+        let shift = m.leading_zeros();
+        let d = m << shift;
+        let (hi, lo) = if shift == 0 { (hi, lo) } else { ((hi << shift) | (lo >> (128 - shift)), lo << shift) };
+        let r = rem_192_by_128(hi, (lo >> 64) as u64, d);
+        rem_192_by_128(r, lo as u64, d) >> shift
+    }
+
+    fn is_prime(&self) -> bool {
+        match u64::try_from(*self) {
+            Ok(n) => factor::is_prime(n),
+            Err(_) => is_prime_generic(self),
+        }
+    }
+
+    fn factor_into(&self, primes: &mut Vec<Self>) {
+        match u64::try_from(*self) {
+            Ok(n) => {
+                let mut factors = Vec::new();
+                n.factor_into(&mut factors);
+                primes.extend(factors.into_iter().map(u128::from));
+            }
+            Err(_) => pollard_rho(self, primes),
+        }
+    }
+}
+
+#[cfg(feature = "bigint")]
+impl UInt for num_bigint::BigUint {
+    fn mul_mod(&self, rhs: &Self, modulus: &Self) -> Self {
+        if num_traits::Zero::is_zero(modulus) {
+            return num_traits::Zero::zero();
+        }
+        self * rhs % modulus
+    }
+
+    fn pow_mod(&self, exp: &Self, modulus: &Self) -> Self {
+        if num_traits::Zero::is_zero(modulus) {
+            return num_traits::Zero::zero();
+        }
+        self.modpow(exp, modulus)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn u128_primality_and_factorization() {
+        // 2^64 + 13 は素数
+        let p = (1u128 << 64) + 13;
+        assert!(p.is_prime());
+        assert!(!(p * 3).is_prime());
+        assert!(u128::MAX.mul_mod(&u128::MAX, &p) < p);
+        let n = p * 1_000_000_007 * 8;
+        let mut factors = Vec::new();
+        prime_factors(n, &mut factors);
+        assert_eq!(factors, [(2, 3), (1_000_000_007, 1), (p, 1)]);
+        // 40bit程度の素数2つの積。rhoが約2^20歩かかる
+        let (r, q) = (1_099_511_627_791u128, 2_199_023_255_579u128);
+        let mut factors = Vec::new();
+        prime_factors(q * r, &mut factors);
+        assert_eq!(factors, [(r, 1), (q, 1)]);
+        let mut factors = Vec::new();
+        prime_factors(720u32, &mut factors);
+        assert_eq!(factors, [(2, 4), (3, 2), (5, 1)]);
+    }
+
+    #[test]
+    fn u128_mul_mod() {
+        use num_bigint::BigUint;
+        let moduli = [3, u64::MAX as u128, (1 << 64) + 13, (1 << 100) + 277, u128::MAX - 158, u128::MAX, 1 << 127];
+        let values = [0, 1, 2, u64::MAX as u128, 1 << 64, (1 << 127) - 1, u128::MAX - 1, u128::MAX, 0x0123_4567_89ab_cdef_fedc_ba98_7654_3210];
+        for m in moduli {
+            for a in values {
+                for b in values {
+                    let expected = BigUint::from(a) * BigUint::from(b) % BigUint::from(m);
+                    assert_eq!(BigUint::from(a.mul_mod(&b, &m)), expected, "{a} * {b} mod {m}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn bpsw() {
+        // 最初の13個の素数を底とする最小の強擬素数。ここからはBPSW判定法になる
+        let psi13 = MR_DETERMINISTIC_BOUND;
+        assert!(BASES.iter().all(|&a| strong_probable_prime(&psi13, &a.into())));
+        assert!(!psi13.is_prime());
+        // 強いリュカ擬素数 (Selfridgeのパラメータ)
+        for n in [5459u64, 5777, 10877, 16109, 18971] {
+            assert!(strong_lucas_probable_prime(&n), "{n}");
+            assert!(!strong_probable_prime(&n, &2), "{n}");
+        }
+        for n in (3..200_000u64).step_by(2) {
+            let expected = factor::is_prime(n);
+            assert_eq!(strong_probable_prime(&n, &2) && strong_lucas_probable_prime(&n), expected, "{n}");
+        }
+        let (m61, m89, m127) = ((1u128 << 61) - 1, (1u128 << 89) - 1, (1u128 << 127) - 1);
+        assert!(m89.is_prime() && m127.is_prime());
+        assert!(!(m61 * m61).is_prime());
+        assert!(!(m61 * ((1 << 64) + 13)).is_prime());
+        assert!(!((1u128 << 89) + 1).is_prime());
+    }
+
+    #[test]
+    fn inverses() {
+        for m in 1u32..=60 {
+            for a in 0..m {
+                let expected = (0..m).find(|&x| a * x % m == 1 % m);
+                assert_eq!(inverse(&a, &m), expected, "{a}^-1 mod {m}");
+            }
+        }
+        let m = u128::MAX - 158;
+        let a = u128::MAX / 3;
+        assert_eq!(inverse(&a, &m).unwrap().mul_mod(&a, &m), 1);
+    }
+}